@@ -0,0 +1,1122 @@
+use crate::gomoku::ascii;
+use crate::gomoku::catalogue;
+use crate::gomoku::polyomino::{self, Equivalence};
+use crate::gomoku::preset::Preset;
+use crate::gomoku::{
+    self, Chances, Engine, Goal, Grid, Lattice, Limits, Mode, Opening, Outcome, Placement, Rules,
+    Schedule, Shape, Solution, Topology, Turns, LAYER,
+};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The most cells a board may have. Setting up a search takes time and
+/// memory that grow faster than the board, before any node limit applies.
+const MAX_CELLS: i64 = 1024;
+
+const USAGE: &str = "\
+Usage: gomoku <command> [options]
+
+Commands:
+  solve      Solve the position and print the game-theoretic value
+  play       Play the game interactively on the terminal
+  analyze    Solve every reply to the position
+  sweep      Solve the position for a range of board sizes or shapes
+  shapes     List the shape catalogue or enumerate polyforms of the grid
+
+Options:
+  -n, --size <N|WxH>      Board size, square or width x height, on cubes also
+                          WxHxD with D layers, at most 1024 cells (default 5,
+                          cubes N deep)
+      --sizes <RANGE>     Board sizes for sweep, squares A..B or rectangles
+                          AxB..CxD (default 3..size)
+      --grid <GRID>       square, hex, triangle or cube cells (default square),
+                          hex boards are rhombi in axial coordinates, triangle
+                          x,y points up when x + y is even and cube cells are
+                          written x,y,z
+      --topology <TOP>    plane, cylinder (left and right edges meet) or torus
+                          (default plane)
+      --game <GAME>       k in a row on an m x n board, rows, columns and
+                          diagonals counting, as m,n,k or one of tic-tac-toe
+                          (3,3,3), freestyle-gomoku (15,15,5),
+                          standard-gomoku (exactly five), renju (Black,
+                          player one, needs exactly five and may not play
+                          overlines, double-fours or double-threes),
+                          pente (19,19,5 with five captures winning) or
+                          qubic (4,4,4 on the cube grid, where the lines
+                          through the layers and the diagonals of space
+                          count too, a known first-player win still out of
+                          the search's reach), sets the size, shapes,
+                          exactness and captures unless they are given
+      --shape <SHAPE>     Shape of both players (default skinny, bar on hex,
+                          triamond on triangles)
+      --shape1 <SHAPE>    Shape of player one
+      --shape2 <SHAPE>    Shape of player two
+      --goal <GOAL>       achievement (forming your shape wins), avoidance
+                          (forming it loses), score (the board fills up and
+                          more copies of your shape win) or disjoint-score
+                          (the most copies sharing no cell count) (default
+                          achievement)
+      --mode <MODE>       strong (both players build), maker-breaker or
+                          breaker-maker (only Maker builds, Breaker wins a
+                          full board; the first named moves first), or
+                          avoider-enforcer and enforcer-avoider, the same
+                          with the avoidance goal, or picker-chooser and
+                          chooser-picker (player one, Picker, offers two cells
+                          and Chooser keeps one; the first named builds),
+                          and waiter-client or client-waiter (Chooser has
+                          to avoid or to form the shape) (default strong)
+      --stones <STONES>   Stones per turn, Q every turn, P,Q for P on the
+                          first turn (Connect6 is 1,2) or A:B for A stones of
+                          player one and B of player two (default 1)
+      --turns <TURNS>     alternating or random, where a fair coin decides who
+                          places each stone and the solver finds each
+                          player's exact chance to win (default alternating)
+      --placement <PL>    free or gravity, where stones drop to the lowest
+                          empty cell of their column as in Connect Four
+                          (default free)
+      --opening <OPEN>    free, centre (the first stone on the centre cell),
+                          pro or long-pro (also player one's second stone 3
+                          or 4 rows or columns from the centre), swap (player
+                          two may take over the first stone) or swap2 (player
+                          one places two stones of their own and one of the
+                          opponent's, player two takes over player one's
+                          side, answers or places one more stone of each and
+                          lets player one pick a side) (default free), moves
+                          never swap but the solver searches the swaps
+      --copies <K>        Disjoint copies of their shapes a player has to form
+                          (default 1)
+      --exact <PLAYERS>   none, one, two or both, the players whose shapes
+                          only count without an own stone continuing one of
+                          their lines, like an overline in gomoku (default
+                          none)
+      --captures <N>      Pairs a player wins by capturing, a stone flanking
+                          exactly two enemy stones in a line takes them off
+                          the board as in Pente (default 0, no captures)
+      --cells <N>         Use every polyomino with N cells, for shapes and sweep
+      --equivalence <EQ>  free, one-sided or fixed polyominoes (default free)
+  -m, --moves <MOVES>     Opening moves, e.g. \"2,2 2,3\" or \"1,1,1 2,1,1\", turns
+                          may be separated by semicolons as in \"2,2; 2,3 1,1\"
+  -b, --board <BOARD>     Start position as a grid like \".X./O../..#\" or a
+                          file containing one, sets the size, blocked cells
+                          (#), wrapping edges (~) and moves
+  -e, --engine <ENGINE>   pns, minimax or alphabeta (default pns)
+      --max-nodes <N>     Give up after visiting N nodes
+      --time-limit <SECS> Give up after SECS seconds
+  -f, --format <FORMAT>   text, csv or json (default text)
+  -h, --help              Print this help
+
+A SHAPE is either a catalogue name, a list of cells, e.g. \"0,0 1,0 1,1\", or a
+grid, e.g. \"##./.##\", or several of them separated by bars, e.g. \"el|tee\",
+any of which may be formed. Hex shapes and boards are drawn with each row shifted
+half a cell further right, e.g. \"# #/ # .\" is the triangle. Cube shapes and
+boards list their layers separated by empty rows, e.g. \"##//#.\" is the tripod.";
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownCommand(String),
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue(String, String, String),
+    Game(gomoku::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Solve,
+    Play,
+    Analyze,
+    Sweep,
+    Shapes,
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    size: (i32, i32),
+    /// Layers of cube boards, as deep as they are wide unless given.
+    depth: Option<i32>,
+    /// Cells taken off the board by `--board`, for every size.
+    blocked: Vec<(i32, i32)>,
+    topology: Topology,
+    lattice: Lattice,
+    sizes: Option<Vec<(i32, i32)>>,
+    /// Alternative shapes of each player, any of which counts.
+    shapes1: Vec<Vec<(i32, i32)>>,
+    shapes2: Vec<Vec<(i32, i32)>>,
+    /// The k-in-a-row game giving the defaults of size, shapes and rules.
+    game: Option<Preset>,
+    rules: Rules,
+    cells: Option<usize>,
+    equivalence: Equivalence,
+    moves: Vec<(i32, i32)>,
+    board: Option<String>,
+    engine: Engine,
+    limits: Limits,
+    format: Format,
+}
+
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let options = parse(args)?;
+    match options.command {
+        Command::Help => println!("{USAGE}"),
+        Command::Shapes => match options.cells {
+            Some(cells) => print_polyominoes(
+                &options,
+                &polyomino::enumerate(cells, options.equivalence, options.lattice),
+            ),
+            None => print_shapes(options.format, options.lattice),
+        },
+        Command::Play => gomoku::_play(
+            &options.grid(options.size),
+            &options.shapes1,
+            &options.shapes2,
+            options.rules,
+            options.moves,
+        )?,
+        Command::Solve => {
+            let solution = gomoku::solve(
+                &options.grid(options.size),
+                &options.shapes1,
+                &options.shapes2,
+                options.rules,
+                options.moves.clone(),
+                options.engine,
+                options.limits,
+            )?;
+            let label = options.shapes_label(&options.shapes1, &options.shapes2);
+            print_solutions(&options, &[(label, options.size, solution)]);
+        }
+        Command::Sweep => {
+            let family = match options.cells {
+                Some(cells) => polyomino::enumerate(cells, options.equivalence, options.lattice)
+                    .into_iter()
+                    .map(|shape| (vec![shape.clone()], vec![shape]))
+                    .collect(),
+                None => vec![(options.shapes1.clone(), options.shapes2.clone())],
+            };
+            let sizes = match (&options.sizes, options.cells) {
+                (Some(sizes), _) => sizes.clone(),
+                (None, Some(_)) => vec![options.size],
+                (None, None) => (3..=options.size.0.max(3))
+                    .map(|size| (size, size))
+                    .collect(),
+            };
+            let mut solutions = vec![];
+            for (shapes1, shapes2) in family {
+                for size in &sizes {
+                    let solution = gomoku::solve(
+                        &options.grid(*size),
+                        &shapes1,
+                        &shapes2,
+                        options.rules,
+                        options.moves.clone(),
+                        options.engine,
+                        options.limits,
+                    )?;
+                    solutions.push((options.shapes_label(&shapes1, &shapes2), *size, solution));
+                }
+            }
+            print_solutions(&options, &solutions);
+        }
+        Command::Analyze => {
+            let results = gomoku::analyze(
+                &options.grid(options.size),
+                &options.shapes1,
+                &options.shapes2,
+                options.rules,
+                options.moves.clone(),
+                options.engine,
+                options.limits,
+            )?;
+            print_analysis(&options, &results);
+        }
+    }
+    Ok(())
+}
+
+fn parse(args: &[String]) -> Result<Options, Error> {
+    let mut options = Options {
+        command: Command::Help,
+        size: (5, 5),
+        depth: None,
+        blocked: vec![],
+        topology: Topology::Plane,
+        lattice: Lattice::Square,
+        sizes: None,
+        shapes1: vec![],
+        shapes2: vec![],
+        game: None,
+        rules: Rules::default(),
+        cells: None,
+        equivalence: Equivalence::Free,
+        moves: vec![],
+        board: None,
+        engine: Engine::Pns,
+        limits: Limits::default(),
+        format: Format::Text,
+    };
+    // shapes and moves are read once the lattice is known
+    let mut shapes: [Option<(&String, &String)>; 2] = [None, None];
+    let mut moves: Option<(&String, &String)> = None;
+    let (mut sized, mut gridded, mut exact, mut captures) = (false, false, false, false);
+    let mut args = args.iter();
+    if let Some(command) = args.next() {
+        options.command = match command.as_str() {
+            "solve" => Command::Solve,
+            "play" => Command::Play,
+            "analyze" => Command::Analyze,
+            "sweep" => Command::Sweep,
+            "shapes" => Command::Shapes,
+            "help" | "-h" | "--help" => return Ok(options),
+            _ => return Err(Error::UnknownCommand(command.clone())),
+        };
+    }
+    while let Some(option) = args.next() {
+        if option == "-h" || option == "--help" {
+            options.command = Command::Help;
+            return Ok(options);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| Error::MissingValue(option.clone()))?;
+        let invalid = |reason: String| Error::InvalidValue(option.clone(), value.clone(), reason);
+        match option.as_str() {
+            "-n" | "--size" => {
+                (options.size, options.depth) = parse_size(value).map_err(invalid)?;
+                sized = true;
+            }
+            "--game" => options.game = Some(value.parse().map_err(invalid)?),
+            "--sizes" => options.sizes = Some(parse_range(value).map_err(invalid)?),
+            "--topology" => options.topology = value.parse().map_err(invalid)?,
+            "--grid" => {
+                options.lattice = value.parse().map_err(invalid)?;
+                gridded = true;
+            }
+            "--shape" => shapes = [Some((option, value)); 2],
+            "--shape1" => shapes[0] = Some((option, value)),
+            "--shape2" => shapes[1] = Some((option, value)),
+            "--goal" => options.rules.goal = value.parse().map_err(invalid)?,
+            "--mode" => parse_mode(value, &mut options.rules).map_err(invalid)?,
+            "--stones" => options.rules.schedule = value.parse().map_err(invalid)?,
+            "--turns" => options.rules.turns = value.parse().map_err(invalid)?,
+            "--placement" => options.rules.placement = value.parse().map_err(invalid)?,
+            "--opening" => options.rules.opening = value.parse().map_err(invalid)?,
+            "--exact" => {
+                options.rules.exact = value.parse().map_err(invalid)?;
+                exact = true;
+            }
+            "--copies" => {
+                options.rules.copies = parse_number(value).map_err(invalid)?;
+                if options.rules.copies == 0 {
+                    return Err(invalid("expected at least one copy".to_string()));
+                }
+            }
+            "--captures" => {
+                options.rules.captures = parse_number(value).map_err(invalid)?;
+                captures = true;
+            }
+            "--cells" => options.cells = Some(parse_number(value).map_err(invalid)?),
+            "--equivalence" => options.equivalence = value.parse().map_err(invalid)?,
+            "-m" | "--moves" => moves = Some((option, value)),
+            "-b" | "--board" => options.board = Some(value.clone()),
+            "-e" | "--engine" => options.engine = value.parse().map_err(invalid)?,
+            "--max-nodes" => options.limits.max_nodes = Some(parse_number(value).map_err(invalid)?),
+            "--time-limit" => {
+                let secs: f64 = parse_number(value).map_err(invalid)?;
+                if !secs.is_finite() || secs < 0.0 {
+                    return Err(invalid(
+                        "expected a non-negative number of seconds".to_string(),
+                    ));
+                }
+                options.limits.max_time = Some(Duration::from_secs_f64(secs));
+            }
+            "-f" | "--format" => options.format = value.parse().map_err(invalid)?,
+            _ => return Err(Error::UnknownOption(option.clone())),
+        }
+    }
+    if options.rules.turns == Turns::Random
+        && (options.rules.mode.offers() || options.rules.schedule != Schedule::default())
+    {
+        return Err(Error::InvalidValue(
+            "--turns".to_string(),
+            options.rules.turns.to_string(),
+            "a coin decides every single stone, which leaves no room for schedules or offers"
+                .to_string(),
+        ));
+    }
+    if options.rules.mode.offers() && options.rules.schedule != Schedule::default() {
+        return Err(Error::InvalidValue(
+            "--stones".to_string(),
+            options.rules.schedule.to_string(),
+            "offers always hand out one stone to each player".to_string(),
+        ));
+    }
+    if let Some(game) = options.game {
+        if game.cube && !gridded {
+            options.lattice = Lattice::Cube;
+        }
+        let lattice = match game.cube {
+            true => options.lattice == Lattice::Cube,
+            false => matches!(options.lattice, Lattice::Square | Lattice::Cube),
+        };
+        if !lattice {
+            return Err(Error::InvalidValue(
+                "--game".to_string(),
+                format!("{},{},{}", game.width, game.height, game.k),
+                "lines of k in a row need the diagonals of the square grid or the cube".to_string(),
+            ));
+        }
+        if !sized {
+            options.size = (game.width, game.height);
+        }
+        if !exact {
+            options.rules.exact = game.exact;
+        }
+        options.rules.renju = game.renju;
+        options.rules.lines = shapes == [None, None] && options.cells.is_none();
+        if !captures {
+            options.rules.captures = game.captures;
+        }
+    }
+    if options.rules.captures > 0
+        && (options.rules.mode != Mode::Strong || options.rules.goal != Goal::Achievement)
+    {
+        return Err(Error::InvalidValue(
+            "--captures".to_string(),
+            options.rules.captures.to_string(),
+            "captures win the strong game, where both players build their shapes".to_string(),
+        ));
+    }
+    if options.rules.goal.scores()
+        && (options.rules.mode != Mode::Strong || options.rules.renju || options.rules.copies != 1)
+    {
+        return Err(Error::InvalidValue(
+            "--goal".to_string(),
+            options.rules.goal.to_string(),
+            "both players score their copies one by one, Black as freely as White".to_string(),
+        ));
+    }
+    if options.rules.renju
+        && (options.rules.turns == Turns::Random
+            || options.rules.mode.offers()
+            || options.rules.schedule != Schedule::default())
+    {
+        return Err(Error::InvalidValue(
+            "--game".to_string(),
+            "renju".to_string(),
+            "Black and White alternate single stones in Renju".to_string(),
+        ));
+    }
+    if options.rules.placement == Placement::Gravity
+        && (options.lattice != Lattice::Square
+            || options.topology == Topology::Torus
+            || options.rules.mode.offers())
+    {
+        return Err(Error::InvalidValue(
+            "--placement".to_string(),
+            options.rules.placement.to_string(),
+            "stones drop down the columns of a square grid that does not wrap top to bottom, one at a time"
+                .to_string(),
+        ));
+    }
+    let opening = options.rules.opening;
+    let reason = match opening {
+        Opening::Free => None,
+        _ if options.rules.turns == Turns::Random || options.rules.mode.offers() => {
+            Some("openings need the players to take turns")
+        }
+        Opening::Swap | Opening::Swap2 if options.rules.mode != Mode::Strong => {
+            Some("swaps need both players to build")
+        }
+        Opening::Swap | Opening::Swap2 if options.rules.renju => {
+            Some("swaps would hand White the moves forbidden to Black")
+        }
+        Opening::Swap2 if options.rules.schedule != Schedule::default() => {
+            Some("swap2 places single stones")
+        }
+        Opening::Centre | Opening::Pro { .. } if options.rules.placement == Placement::Gravity => {
+            Some("the centre cell has empty cells below it")
+        }
+        Opening::Pro { .. } if options.lattice != Lattice::Square => {
+            Some("pro counts rows and columns of the square grid")
+        }
+        _ => None,
+    };
+    if let Some(reason) = reason {
+        return Err(Error::InvalidValue(
+            "--opening".to_string(),
+            opening.to_string(),
+            reason.to_string(),
+        ));
+    }
+    if options.depth.is_some_and(|depth| depth != 1) && options.lattice != Lattice::Cube {
+        let (width, height) = options.size;
+        return Err(Error::InvalidValue(
+            "--size".to_string(),
+            format!("{width}x{height}x{}", options.depth.unwrap_or(1)),
+            "only cube boards have layers".to_string(),
+        ));
+    }
+    // cube boards are as deep as they are wide unless told otherwise
+    let sizes = options.sizes.iter().flatten().chain([&options.size]);
+    for (width, height) in sizes {
+        let depth = options.depth((*width, *height));
+        check_cells((*width, *height), depth).map_err(|reason| {
+            Error::InvalidValue(
+                "--size".to_string(),
+                format!("{width}x{height}x{depth}"),
+                reason,
+            )
+        })?;
+    }
+    if let Some((option, value)) = moves {
+        options.moves = parse_record(value, options.lattice, options.rules)
+            .map_err(|reason| Error::InvalidValue(option.clone(), value.clone(), reason))?;
+    }
+    let default = match options.lattice {
+        Lattice::Square | Lattice::Cube => "skinny",
+        Lattice::Hex => "bar",
+        Lattice::Triangle => "triamond",
+    };
+    let [shapes1, shapes2] = shapes.map(|shape| match shape {
+        Some((option, value)) => parse_shapes(value, options.lattice)
+            .map_err(|reason| Error::InvalidValue(option.clone(), value.clone(), reason)),
+        None => Ok(match options.game {
+            Some(game) => game.shapes(),
+            None => vec![catalogue::lookup(default, options.lattice)
+                .expect("The default shapes are catalogued.")
+                .shape()],
+        }),
+    });
+    (options.shapes1, options.shapes2) = (shapes1?, shapes2?);
+    if let Some(board) = &options.board {
+        let text = std::fs::read_to_string(board).unwrap_or_else(|_| board.clone());
+        let invalid = |err: ascii::ParseError| {
+            Error::InvalidValue("--board".to_string(), board.clone(), err.to_string())
+        };
+        let (grid, moves) = gomoku::read_position(
+            &text,
+            &options.shapes1,
+            &options.shapes2,
+            options.lattice,
+            options.rules,
+        )
+        .map_err(invalid)?;
+        check_cells((grid.width, grid.height), grid.depth)
+            .map_err(|reason| Error::InvalidValue("--board".to_string(), board.clone(), reason))?;
+        options.size = (grid.width, grid.height);
+        options.depth = Some(grid.depth);
+        options.blocked = grid.blocked.into_iter().collect();
+        options.topology = grid.topology;
+        options.moves = moves;
+    }
+    Ok(options)
+}
+
+impl Options {
+    fn grid(&self, (width, height): (i32, i32)) -> Grid {
+        Grid {
+            depth: self.depth((width, height)),
+            topology: self.topology,
+            lattice: self.lattice,
+            ..Grid::rectangle(width, height)
+        }
+        .with_blocked(self.blocked.iter().copied())
+    }
+
+    fn depth(&self, (width, _): (i32, i32)) -> i32 {
+        match (self.depth, self.lattice) {
+            (Some(depth), _) => depth,
+            (None, Lattice::Cube) => width,
+            (None, _) => 1,
+        }
+    }
+
+    /// Names the shapes of a game, after the k-in-a-row game they come from
+    /// or after the catalogue where possible.
+    fn shapes_label(&self, shapes1: &[Vec<(i32, i32)>], shapes2: &[Vec<(i32, i32)>]) -> String {
+        match self.game {
+            Some(game) if self.rules.lines => game.label(),
+            _ => shapes_label(shapes1, shapes2, self.lattice),
+        }
+    }
+
+    /// The size of a board as `WxH`, or `WxHxD` on the cube lattice.
+    fn size_label(&self, (width, height): (i32, i32)) -> String {
+        match self.lattice {
+            Lattice::Cube => format!("{width}x{height}x{}", self.depth((width, height))),
+            _ => format!("{width}x{height}"),
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| "expected a number".to_string())
+}
+
+/// Parses a board size written as `N` for squares, `WxH` for rectangles or
+/// `WxHxD` for boxes, returning the depth only if it is given.
+fn parse_size(value: &str) -> Result<((i32, i32), Option<i32>), String> {
+    let parts: Vec<&str> = value.split('x').collect();
+    let (size, depth) = match parts[..] {
+        [size] => {
+            let size = parse_number(size)?;
+            ((size, size), None)
+        }
+        [width, height] => ((parse_number(width)?, parse_number(height)?), None),
+        [width, height, depth] => (
+            (parse_number(width)?, parse_number(height)?),
+            Some(parse_number(depth)?),
+        ),
+        _ => return Err("expected a size like 5, 5x4 or 4x4x4".to_string()),
+    };
+    if size.0 < 1 || size.1 < 1 || depth.is_some_and(|depth| depth < 1) {
+        return Err("width, height and depth must be at least 1".to_string());
+    }
+    check_cells(size, depth.unwrap_or(1))?;
+    Ok((size, depth))
+}
+
+/// Whether a board of the size stays within `MAX_CELLS`.
+fn check_cells((width, height): (i32, i32), depth: i32) -> Result<(), String> {
+    match width as i64 * height as i64 * depth as i64 > MAX_CELLS {
+        true => Err(format!("boards have at most {MAX_CELLS} cells")),
+        false => Ok(()),
+    }
+}
+
+/// Parses `A..B` into the squares from A to B, or `AxB..CxD` into all
+/// rectangles from A to C wide and B to D high.
+fn parse_range(value: &str) -> Result<Vec<(i32, i32)>, String> {
+    let (from, to) = value
+        .split_once("..")
+        .ok_or_else(|| "expected a range like 3..6 or 3x4..5x6".to_string())?;
+    let (from, to) = match (parse_size(from)?, parse_size(to)?) {
+        ((from, None), (to, None)) => (from, to),
+        _ => return Err("sweeps only vary width and height, set the depth with -n".to_string()),
+    };
+    if to.0 < from.0 || to.1 < from.1 {
+        return Err("the range is empty".to_string());
+    }
+    if !value.contains('x') {
+        return Ok((from.0..=to.0).map(|size| (size, size)).collect());
+    }
+    let mut sizes = vec![];
+    for width in from.0..=to.0 {
+        for height in from.1..=to.1 {
+            sizes.push((width, height));
+        }
+    }
+    Ok(sizes)
+}
+
+/// Parses cells written as `x,y` pairs, or `x,y,z` on the cube lattice,
+/// separated by whitespace or semicolons.
+fn parse_cells(value: &str, lattice: Lattice) -> Result<Vec<(i32, i32)>, String> {
+    value
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|cell| !cell.is_empty())
+        .map(|cell| {
+            let coordinates = cell
+                .split(',')
+                .map(parse_number)
+                .collect::<Result<Vec<i32>, String>>()?;
+            let (x, y, z) = match (&coordinates[..], lattice) {
+                (&[x, y], _) => (x, y, 0),
+                (&[x, y, z], Lattice::Cube) => (x, y, z),
+                (_, Lattice::Cube) => {
+                    return Err(format!("cell '{cell}' is not of the form x,y,z"))
+                }
+                _ => return Err(format!("cell '{cell}' is not of the form x,y")),
+            };
+            if lattice == Lattice::Cube && [x, y, z].iter().any(|c| c.abs() >= LAYER / 2) {
+                return Err(format!("cell '{cell}' is too far out"));
+            }
+            Ok(lattice.cell((x, y, z)))
+        })
+        .collect()
+}
+
+/// Parses a game record, the moves in order with turns separated by
+/// semicolons. Every turn but the last has to place all of its stones, or
+/// list the two cells offered and the one chosen when turns are offers.
+fn parse_record(value: &str, lattice: Lattice, rules: Rules) -> Result<Vec<(i32, i32)>, String> {
+    if !value.contains(';') {
+        return parse_cells(value, lattice);
+    }
+    let turns: Vec<&str> = value.split(';').collect();
+    let sizes: Box<dyn Iterator<Item = usize>> = match rules.mode.offers() {
+        true => Box::new(std::iter::repeat(3)),
+        false => Box::new(rules.schedule.turns()),
+    };
+    let mut moves = vec![];
+    for (number, (turn, size)) in turns.iter().zip(sizes).enumerate() {
+        let cells = parse_cells(turn, lattice)?;
+        if cells.len() > size || (cells.len() < size && number + 1 < turns.len()) {
+            return Err(format!(
+                "turn {} lists {} cells, expected {size}",
+                number + 1,
+                cells.len()
+            ));
+        }
+        moves.extend(cells);
+    }
+    Ok(moves)
+}
+
+/// Sets the mode, where `avoider-enforcer` and `enforcer-avoider` are the
+/// Maker–Breaker modes with the avoidance goal, `waiter-client` is
+/// Chooser–Picker with the avoidance goal and `client-waiter` another name
+/// for Chooser–Picker.
+fn parse_mode(value: &str, rules: &mut Rules) -> Result<(), String> {
+    let (mode, goal) = match value {
+        "avoider-enforcer" => (Mode::MakerBreaker { maker_first: true }, Goal::Avoidance),
+        "enforcer-avoider" => (Mode::MakerBreaker { maker_first: false }, Goal::Avoidance),
+        "waiter-client" => (
+            Mode::PickerChooser {
+                picker_builds: false,
+            },
+            Goal::Avoidance,
+        ),
+        "client-waiter" => (
+            Mode::PickerChooser {
+                picker_builds: false,
+            },
+            Goal::Achievement,
+        ),
+        _ => {
+            rules.mode = value.parse()?;
+            return Ok(());
+        }
+    };
+    (rules.mode, rules.goal) = (mode, goal);
+    Ok(())
+}
+
+/// Reads alternative shapes separated by bars.
+fn parse_shapes(value: &str, lattice: Lattice) -> Result<Vec<Vec<(i32, i32)>>, String> {
+    value
+        .split('|')
+        .map(|shape| parse_shape(shape.trim(), lattice))
+        .collect()
+}
+
+fn parse_shape(value: &str, lattice: Lattice) -> Result<Vec<(i32, i32)>, String> {
+    if let Some(animal) = catalogue::lookup(value, lattice) {
+        return Ok(animal.shape());
+    }
+    if value.contains(['#', '▲', '▼']) {
+        return ascii::parse_shape_cells(value, lattice).map_err(|err| err.to_string());
+    }
+    if !value.contains(',') {
+        return Err(format!(
+            "unknown {lattice} shape '{value}', see the shapes command"
+        ));
+    }
+    let shape = Shape::new(parse_cells(value, lattice)?, lattice).map_err(|err| err.to_string())?;
+    Ok(shape.cells().to_vec())
+}
+
+fn outcome_key(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::One => "one",
+        Outcome::Two => "two",
+        Outcome::Draw => "draw",
+        Outcome::Chance => "chance",
+        Outcome::Unknown => "unknown",
+    }
+}
+
+/// The winning chances of player one and two, empty unless the turns are random.
+fn chances_fields(solution: &Solution) -> [String; 2] {
+    match solution.chances {
+        Some(Chances { one, two }) => [one.to_string(), two.to_string()],
+        None => [String::new(), String::new()],
+    }
+}
+
+/// The winning chances as JSON fields, null unless the turns are random.
+fn chances_json(solution: &Solution) -> String {
+    let [one, two] = chances_fields(solution).map(|chance| match chance.is_empty() {
+        true => "null".to_string(),
+        false => format!("\"{chance}\""),
+    });
+    format!("\"chance_one\":{one},\"chance_two\":{two}")
+}
+
+/// The score margin of player one as a JSON value, null unless the goal scores.
+fn margin_json(solution: &Solution) -> String {
+    solution
+        .margin
+        .map_or("null".to_string(), |margin| margin.to_string())
+}
+
+/// Names the shapes of a game after the catalogue where possible.
+fn shapes_label(
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    lattice: Lattice,
+) -> String {
+    let label = |shapes: &[Vec<(i32, i32)>]| {
+        shapes
+            .iter()
+            .map(|shape| match catalogue::find(shape, lattice) {
+                Some(animal) => animal.name.to_string(),
+                None => cells_string(shape, lattice),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+    let canonical = |shapes: &[Vec<(i32, i32)>]| {
+        shapes
+            .iter()
+            .map(|shape| polyomino::canonical(shape, Equivalence::Free, lattice))
+            .collect::<Vec<_>>()
+    };
+    match canonical(shapes1) == canonical(shapes2) {
+        true => label(shapes1),
+        false => format!("{} vs {}", label(shapes1), label(shapes2)),
+    }
+}
+
+fn cells_string(shape: &[(i32, i32)], lattice: Lattice) -> String {
+    shape
+        .iter()
+        .map(|cell| coordinates(*cell, lattice).join(","))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The coordinates of a cell as written on the command line.
+fn coordinates(cell: (i32, i32), lattice: Lattice) -> Vec<String> {
+    let (x, y, z) = lattice.coordinates(cell);
+    match lattice {
+        Lattice::Cube => vec![x.to_string(), y.to_string(), z.to_string()],
+        _ => vec![x.to_string(), y.to_string()],
+    }
+}
+
+fn print_solutions(options: &Options, solutions: &[(String, (i32, i32), Solution)]) {
+    let (topology, lattice) = (options.topology, options.lattice);
+    let Rules {
+        goal,
+        mode,
+        schedule,
+        turns,
+        placement,
+        opening,
+        copies,
+        exact,
+        renju,
+        captures,
+        lines: _,
+    } = options.rules;
+    match options.format {
+        Format::Text => {
+            let mut surface = String::new();
+            if lattice != Lattice::Square {
+                surface.push_str(&format!(" {lattice}"));
+            }
+            if topology != Topology::Plane {
+                surface.push_str(&format!(" {topology}"));
+            }
+            // the shapes already name the lines of a game
+            if (Rules {
+                lines: false,
+                ..options.rules
+            }) != Rules::default()
+            {
+                surface.push_str(&format!(" ({})", options.rules));
+            }
+            for (shape, size, solution) in solutions {
+                let outcome = match solution.chances {
+                    Some(Chances { one, two }) => {
+                        format!("player 1 wins with {one}, player 2 with {two}")
+                    }
+                    None => solution.outcome.to_string(),
+                };
+                let outcome = match solution.margin {
+                    Some(margin) if margin != 0 => format!("{outcome} by {}", margin.abs()),
+                    _ => outcome,
+                };
+                println!(
+                    "{shape} on {}{surface}: {outcome} ({} nodes)",
+                    options.size_label(*size),
+                    solution.nodes
+                );
+            }
+        }
+        Format::Csv => {
+            println!("shape,width,height,depth,grid,topology,goal,mode,stones,turns,placement,opening,copies,exact,renju,captures,outcome,chance_one,chance_two,margin,nodes");
+            // schedules like 1,2 hold a comma
+            let stones = match schedule.to_string() {
+                stones if stones.contains(',') => format!("\"{stones}\""),
+                stones => stones,
+            };
+            for (shape, (width, height), solution) in solutions {
+                let [one, two] = chances_fields(solution);
+                let margin = solution
+                    .margin
+                    .map_or(String::new(), |margin| margin.to_string());
+                println!(
+                    "{shape},{width},{height},{},{lattice},{topology},{goal},{mode},{stones},{turns},{placement},{opening},{copies},{exact},{renju},{captures},{},{one},{two},{margin},{}",
+                    options.depth((*width, *height)),
+                    outcome_key(solution.outcome),
+                    solution.nodes
+                );
+            }
+        }
+        Format::Json => {
+            let rows: Vec<String> = solutions
+                .iter()
+                .map(|(shape, (width, height), solution)| {
+                    format!(
+                        "{{\"shape\":\"{shape}\",\"width\":{width},\"height\":{height},\"depth\":{},\"grid\":\"{lattice}\",\"topology\":\"{topology}\",\"goal\":\"{goal}\",\"mode\":\"{mode}\",\"stones\":\"{schedule}\",\"turns\":\"{turns}\",\"placement\":\"{placement}\",\"opening\":\"{opening}\",\"copies\":{copies},\"exact\":\"{exact}\",\"renju\":{renju},\"captures\":{captures},\"outcome\":\"{}\",{},\"margin\":{},\"nodes\":{}}}",
+                        options.depth((*width, *height)),
+                        outcome_key(solution.outcome),
+                        chances_json(solution),
+                        margin_json(solution),
+                        solution.nodes
+                    )
+                })
+                .collect();
+            println!("[{}]", rows.join(","));
+        }
+    }
+}
+
+fn print_analysis(options: &Options, results: &[((i32, i32), Solution)]) {
+    let lattice = options.lattice;
+    match options.format {
+        Format::Text => {
+            let mut string = String::new();
+            for z in 0..options.depth(options.size) {
+                if z > 0 {
+                    string.push('\n');
+                }
+                for y in 0..options.size.1 {
+                    if lattice == Lattice::Hex {
+                        string.push_str(&" ".repeat(y as usize));
+                    }
+                    for x in 0..options.size.0 {
+                        let cell = lattice.cell((x, y, z));
+                        let c = match results.iter().find(|(cord, _)| *cord == cell) {
+                            Some((_, solution)) => match solution.outcome {
+                                Outcome::One => "1",
+                                Outcome::Two => "2",
+                                Outcome::Draw => "=",
+                                Outcome::Chance => "%",
+                                Outcome::Unknown => "?",
+                            },
+                            None => "#",
+                        };
+                        string.push_str(c);
+                        string.push(' ');
+                    }
+                    string.push('\n');
+                }
+            }
+            print!("{string}");
+            println!(
+                "1: player 1 wins, 2: player 2 wins, =: draw, ?: unknown, #: occupied or blocked"
+            );
+            if options.rules.turns == Turns::Random {
+                println!("%: either player may win, see the csv or json format for the chances");
+            }
+            if options.rules.placement == Placement::Gravity {
+                println!("under gravity only the lowest empty cell of each column is open");
+            }
+        }
+        Format::Csv => {
+            let axes = match lattice {
+                Lattice::Cube => "x,y,z",
+                _ => "x,y",
+            };
+            println!("{axes},outcome,chance_one,chance_two,margin,nodes");
+            for (cell, solution) in results {
+                let [one, two] = chances_fields(solution);
+                let margin = solution
+                    .margin
+                    .map_or(String::new(), |margin| margin.to_string());
+                println!(
+                    "{},{},{one},{two},{margin},{}",
+                    coordinates(*cell, lattice).join(","),
+                    outcome_key(solution.outcome),
+                    solution.nodes
+                );
+            }
+        }
+        Format::Json => {
+            let rows: Vec<String> = results
+                .iter()
+                .map(|(cell, solution)| {
+                    let cords: Vec<String> = ["x", "y", "z"]
+                        .iter()
+                        .zip(coordinates(*cell, lattice))
+                        .map(|(axis, value)| format!("\"{axis}\":{value}"))
+                        .collect();
+                    format!(
+                        "{{{},\"outcome\":\"{}\",{},\"margin\":{},\"nodes\":{}}}",
+                        cords.join(","),
+                        outcome_key(solution.outcome),
+                        chances_json(solution),
+                        margin_json(solution),
+                        solution.nodes
+                    )
+                })
+                .collect();
+            println!("[{}]", rows.join(","));
+        }
+    }
+}
+
+fn print_shapes(format: Format, lattice: Lattice) {
+    let animals = catalogue::animals(lattice);
+    match format {
+        Format::Text => {
+            for animal in animals {
+                let mut name = animal.name.to_string();
+                if !animal.aliases.is_empty() {
+                    name = format!("{name} ({})", animal.aliases.join(", "));
+                }
+                println!(
+                    "{name} [{}]\n{}",
+                    animal.symmetry(),
+                    ascii::shape_to_string(animal.cells, lattice)
+                );
+            }
+        }
+        Format::Csv => {
+            println!("name,aliases,size,symmetry,cells");
+            for animal in animals {
+                println!(
+                    "{},{},{},{},{}",
+                    animal.name,
+                    animal.aliases.join(" "),
+                    animal.size(),
+                    animal.symmetry(),
+                    cells_string(animal.cells, lattice)
+                );
+            }
+        }
+        Format::Json => {
+            let rows: Vec<String> = animals
+                .iter()
+                .map(|animal| {
+                    let aliases: Vec<String> = animal
+                        .aliases
+                        .iter()
+                        .map(|alias| format!("\"{alias}\""))
+                        .collect();
+                    let cells: Vec<String> = animal
+                        .cells
+                        .iter()
+                        .map(|cell| format!("[{}]", coordinates(*cell, lattice).join(",")))
+                        .collect();
+                    format!(
+                        "{{\"name\":\"{}\",\"aliases\":[{}],\"symmetry\":\"{}\",\"cells\":[{}]}}",
+                        animal.name,
+                        aliases.join(","),
+                        animal.symmetry(),
+                        cells.join(",")
+                    )
+                })
+                .collect();
+            println!("[{}]", rows.join(","));
+        }
+    }
+}
+
+fn print_polyominoes(options: &Options, shapes: &[Vec<(i32, i32)>]) {
+    let lattice = options.lattice;
+    let name =
+        |shape: &[(i32, i32)]| catalogue::find(shape, lattice).map_or("", |animal| animal.name);
+    match options.format {
+        Format::Text => {
+            let kind = match lattice {
+                Lattice::Square => "polyominoes",
+                Lattice::Hex => "polyhexes",
+                Lattice::Triangle => "polyiamonds",
+                Lattice::Cube => "polycubes",
+            };
+            println!("{} {kind}\n", shapes.len());
+            for shape in shapes {
+                println!(
+                    "{}\n{}",
+                    name(shape),
+                    ascii::shape_to_string(shape, lattice)
+                );
+            }
+        }
+        Format::Csv => {
+            println!("name,cells");
+            for shape in shapes {
+                println!("{},{}", name(shape), cells_string(shape, lattice));
+            }
+        }
+        Format::Json => {
+            let rows: Vec<String> = shapes
+                .iter()
+                .map(|shape| {
+                    let cells: Vec<String> = shape
+                        .iter()
+                        .map(|cell| format!("[{}]", coordinates(*cell, lattice).join(",")))
+                        .collect();
+                    format!(
+                        "{{\"name\":\"{}\",\"cells\":[{}]}}",
+                        name(shape),
+                        cells.join(",")
+                    )
+                })
+                .collect();
+            println!("[{}]", rows.join(","));
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err("expected one of text, csv, json".to_string()),
+        }
+    }
+}
+
+impl From<gomoku::Error> for Error {
+    fn from(err: gomoku::Error) -> Self {
+        Error::Game(err)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownCommand(command) => {
+                write!(f, "unknown command '{command}', see --help")
+            }
+            Error::UnknownOption(option) => write!(f, "unknown option '{option}', see --help"),
+            Error::MissingValue(option) => write!(f, "option '{option}' needs a value"),
+            Error::InvalidValue(option, value, reason) => {
+                write!(f, "invalid value '{value}' for '{option}': {reason}")
+            }
+            Error::Game(err) => write!(f, "{err}"),
+        }
+    }
+}
@@ -1,87 +1,557 @@
-#![allow(unused)]
-use self::game::Board;
+use self::expectimax::Expectimax;
+pub use self::expectimax::{Chances, Probability};
+use self::game::{Board, Move};
+pub use self::game::{Shape, ShapeError};
 use self::graph::PNS;
+pub use self::grid::{Grid, Lattice, Topology, LAYER};
+pub use self::rules::{Goal, Mode, Opening, Placement, Rules, Schedule, Turns};
 // use self::pns::*;
 use self::tree::Tree;
 use crate::gomoku::game::Tile;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::hash_map::RandomState;
+use std::fmt::Display;
+use std::hash::BuildHasher;
 use std::io;
 use std::num::ParseIntError;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+pub mod ascii;
+pub mod catalogue;
+mod expectimax;
 mod game;
 mod graph;
+mod grid;
+pub mod polyomino;
+pub mod preset;
+mod renju;
+mod rules;
 // mod pns;
 mod tree;
-pub enum _Error {
+
+type Turn = (i32, i32);
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
     IllegalSize,
+    MismatchedSeam,
+    CordOffBoard((i32, i32)),
+    CordOccupied((i32, i32)),
+    CordBlocked((i32, i32)),
+    NotOffered((i32, i32)),
+    IllegalOffer((i32, i32), (i32, i32)),
+    /// Black may not play an overline, double-four or double-three in Renju.
+    Forbidden((i32, i32)),
+    /// Under gravity a stone may not rest above an empty cell.
+    Floating((i32, i32)),
+    /// The opening rules keep the stone off the cell.
+    Opening((i32, i32)),
+    /// Analysis shows single cells, not the offers of a Picker's turn.
+    OfferToMake,
+    GameAlreadyOver,
+    Shape(ShapeError),
+}
+
+/// Result of a solve from the perspective of the player to move first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    One,
+    Two,
+    Draw,
+    /// Either player may win, depending on the coin flips of a random-turn game.
+    Chance,
+    /// The search hit one of its limits before the value was known.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Engine {
+    Pns,
+    Minimax,
+    Alphabeta,
+}
+
+/// Resource limits of a single search. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub max_nodes: Option<usize>,
+    pub max_time: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    pub outcome: Outcome,
+    pub nodes: usize,
+    /// The exact winning chances of random-turn games.
+    pub chances: Option<Chances>,
+    /// How many copies player one ends ahead by in scoring games.
+    pub margin: Option<i32>,
+}
+
+impl Limits {
+    pub fn exceeded(&self, nodes: usize, start: Instant) -> bool {
+        self.max_nodes.is_some_and(|max| nodes > max)
+            || self.max_time.is_some_and(|max| start.elapsed() > max)
+    }
+}
+
+/// Solves the position reached after `moves_made` with the given engine.
+pub fn solve(
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
+    moves_made: Vec<(i32, i32)>,
+    engine: Engine,
+    limits: Limits,
+) -> Result<Solution, Error> {
+    let board = opening(grid, shapes1, shapes2, rules, &moves_made)?;
+    if rules.turns == Turns::Random {
+        // only expectimax copes with the coin flips
+        return Ok(chance_search(board, limits));
+    }
+    if board.is_over() {
+        return Ok(Solution {
+            outcome: Outcome::from(*board.winner()),
+            nodes: 0,
+            chances: None,
+            margin: None,
+        });
+    }
+    Ok(match engine {
+        Engine::Pns => basic_pns(grid, shapes1, shapes2, rules, moves_made, limits)?,
+        Engine::Minimax | Engine::Alphabeta => tree_search(board, engine, limits),
+    })
+}
+
+/// Solves every legal reply to the position reached after `moves_made`.
+pub fn analyze(
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
+    moves_made: Vec<(i32, i32)>,
+    engine: Engine,
+    limits: Limits,
+) -> Result<Vec<(Turn, Solution)>, Error> {
+    let board = opening(grid, shapes1, shapes2, rules, &moves_made)?;
+    if board.is_over() {
+        return Err(Error::GameAlreadyOver);
+    }
+    if board.rules.mode.offers() && board.offered.is_none() {
+        return Err(Error::OfferToMake);
+    }
+    let mut results = vec![];
+    for play in board.moves() {
+        let Move::Stone(cord) = play else {
+            continue;
+        };
+        let mut moves = moves_made.clone();
+        moves.push(cord);
+        let solution = solve(grid, shapes1, shapes2, rules, moves, engine, limits)?;
+        results.push((cord, solution));
+    }
+    Ok(results)
+}
+
+/// Reads a position in the ASCII board format and returns its grid
+/// together with an order of moves reaching it.
+pub fn read_position(
+    text: &str,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    lattice: Lattice,
+    rules: Rules,
+) -> Result<(Grid, Vec<Turn>), ascii::ParseError> {
+    let board = ascii::parse_board(text, shapes1, shapes2, lattice, rules)?;
+    let stones = |tile: Tile| {
+        board
+            .field
+            .iter()
+            .filter(move |(_, t)| **t == tile)
+            .map(|(cord, _)| *cord)
+    };
+    let mut ones: Vec<Turn> = stones(Tile::One).collect();
+    if matches!(rules.opening, Opening::Centre | Opening::Pro { .. }) {
+        // the centre stone first, then the one farthest from it
+        ones.sort_by_key(|cell| (*cell != board.grid.centre(), -board.off_centre(*cell)));
+    }
+    let (mut ones, mut twos) = (ones.into_iter(), stones(Tile::Two));
+    if rules.mode.offers() {
+        // Picker offers their own cell with Chooser's, a lone last cell
+        // goes to Chooser
+        let mut moves = vec![];
+        for two in twos {
+            match ones.next() {
+                Some(one) => moves.extend([one, two, two]),
+                None => moves.push(two),
+            }
+        }
+        return Ok((board.grid, moves));
+    }
+    if rules.placement == Placement::Gravity {
+        return Ok((board.grid.clone(), dropped(&board)));
+    }
+    let moves = (0..board.turn)
+        .filter_map(|stone| match rules.schedule.mover(stone) {
+            Tile::One => ones.next(),
+            _ => twos.next(),
+        })
+        .collect();
+    Ok((board.grid, moves))
+}
+
+/// An order of the stones on a board under gravity: each player drops the
+/// lowest of their stones that has something below it already.
+fn dropped(board: &Board) -> Vec<Turn> {
+    let mut stones: Vec<(Turn, Tile)> = board
+        .field
+        .iter()
+        .filter(|(_, tile)| **tile != Tile::Empty)
+        .map(|(cell, tile)| (*cell, *tile))
+        .collect();
+    stones.sort_by_key(|((x, y), _)| (-y, *x));
+    let mut moves: Vec<Turn> = vec![];
+    for stone in 0..board.turn {
+        let mover = board.rules.schedule.mover(stone);
+        let held = |(x, y): Turn| !board.grid.contains((x, y + 1)) || moves.contains(&(x, y + 1));
+        let next = stones
+            .iter()
+            .position(|(cell, tile)| *tile == mover && held(*cell))
+            .or_else(|| stones.iter().position(|(_, tile)| *tile == mover));
+        if let Some(next) = next {
+            moves.push(stones.remove(next).0);
+        }
+    }
+    moves
+}
+
+/// Sets up a board and plays the opening on it, rejecting illegal sizes and moves.
+fn opening(
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
+    moves_made: &[(i32, i32)],
+) -> Result<Board, Error> {
+    if grid.cells().is_empty() {
+        return Err(Error::IllegalSize);
+    }
+    if !grid.seams_match() {
+        return Err(Error::MismatchedSeam);
+    }
+    let mut board = Board::setup(grid, shapes1, shapes2, rules)?;
+    for play in Move::record(moves_made, rules.mode.offers()) {
+        if board.is_over() {
+            return Err(Error::GameAlreadyOver);
+        }
+        let (cell, result) = match play {
+            Move::Stone(cell) => (cell, board._place_play(cell.0, cell.1)),
+            Move::Offer(a, b) => match board._place_offer(a, b) {
+                Err(game::_Error::_IllegalOffer) => return Err(Error::IllegalOffer(a, b)),
+                // the first of the cells that failed the checks
+                result => (if board._is_open(a) { b } else { a }, result),
+            },
+            _ => unreachable!("Records hold stones and offers."),
+        };
+        result.map_err(|err| match err {
+            game::_Error::_AlreadyOccupied => Error::CordOccupied(cell),
+            game::_Error::_CordBlocked => Error::CordBlocked(cell),
+            game::_Error::_NotOffered => Error::NotOffered(cell),
+            game::_Error::_Forbidden => Error::Forbidden(cell),
+            game::_Error::_Floating => Error::Floating(cell),
+            game::_Error::_Opening => Error::Opening(cell),
+            _ => Error::CordOffBoard(cell),
+        })?;
+    }
+    Ok(board)
 }
 
 pub fn basic_pns(
-    size: i32,
-    shape1: &mut Vec<(i32, i32)>,
-    shape2: &mut Vec<(i32, i32)>,
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
     moves_made: Vec<(i32, i32)>,
-) {
-    let mut pns = PNS::setup(size, shape1, shape2, true, moves_made.clone());
-    let state_is_loss = pns.pns(pns.root);
-    if state_is_loss.0 != 0 {
-        let mut pns = PNS::setup(size, shape1, shape2, false, moves_made);
-        let state_is_win = pns.pns(pns.root);
-        match state_is_win.0 {
-            0 => println!("Draw"),
-            _ => println!("Player 2 wins"),
+    limits: Limits,
+) -> Result<Solution, Error> {
+    if rules.goal.scores() {
+        return score_pns(grid, shapes1, shapes2, rules, moves_made, limits);
+    }
+    let board = opening(grid, shapes1, shapes2, rules, &moves_made)?;
+    let mut nodes = 0;
+    // proof and disproof number of the root, proven when player one wins
+    let mut search = |draw_is_loss: bool| {
+        let mut pns = PNS::setup(
+            grid,
+            shapes1,
+            shapes2,
+            rules,
+            draw_is_loss,
+            moves_made.clone(),
+        )?;
+        pns.limits = limits;
+        let numbers = pns.pns(pns.root);
+        nodes += pns.tree.len();
+        Ok::<_, Error>(numbers)
+    };
+    if let Some(breaker) = rules.mode.breaker() {
+        if rules.goal == Goal::Achievement && board.breaker_wins(breaker.opponent()) {
+            let outcome = Outcome::from(breaker);
+            return Ok(Solution {
+                outcome,
+                nodes,
+                chances: None,
+                margin: None,
+            });
         }
-    } else {
-        println!("Player 1 wins");
+        // somebody wins every game
+        let outcome = match search(true)? {
+            (0, _) => Outcome::One,
+            (_, 0) => Outcome::Two,
+            _ => Outcome::Unknown,
+        };
+        return Ok(Solution {
+            outcome,
+            nodes,
+            chances: None,
+            margin: None,
+        });
     }
+    // A player who cannot win as Maker cannot win at all, their opponent
+    // could play Breaker.
+    let may_win = |tile: Tile| rules.goal == Goal::Avoidance || !board.breaker_wins(tile);
+    if may_win(Tile::One) {
+        match search(true)? {
+            (0, _) => {
+                return Ok(Solution {
+                    outcome: Outcome::One,
+                    nodes,
+                    chances: None,
+                    margin: None,
+                })
+            }
+            (_, 0) => {}
+            _ => {
+                return Ok(Solution {
+                    outcome: Outcome::Unknown,
+                    nodes,
+                    chances: None,
+                    margin: None,
+                })
+            }
+        }
+    }
+    let outcome = match may_win(Tile::Two) {
+        false => Outcome::Draw,
+        true => match search(false)? {
+            (0, _) => Outcome::Draw,
+            (_, 0) => Outcome::Two,
+            _ => Outcome::Unknown,
+        },
+    };
+    Ok(Solution {
+        outcome,
+        nodes,
+        chances: None,
+        margin: None,
+    })
 }
 
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
+/// Solves a scoring game by proving margins: player one can reach the
+/// exact margin but not one more, so the search steps from zero towards it.
+fn score_pns(
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
+    moves_made: Vec<(i32, i32)>,
+    limits: Limits,
+) -> Result<Solution, Error> {
+    let mut nodes = 0;
+    // whether player one ends at least `threshold` copies ahead
+    let mut search = |threshold: i32| {
+        let mut pns = PNS::setup(grid, shapes1, shapes2, rules, true, moves_made.clone())?;
+        pns.limits = limits;
+        pns.threshold = threshold;
+        let numbers = pns.pns(pns.root);
+        nodes += pns.tree.len();
+        Ok::<_, Error>(match numbers {
+            (0, _) => Some(true),
+            (_, 0) => Some(false),
+            _ => None,
+        })
+    };
+    let mut threshold = 0;
+    let margin = match search(threshold)? {
+        Some(true) => loop {
+            threshold += 1;
+            match search(threshold)? {
+                Some(true) => {}
+                Some(false) => break Some(threshold - 1),
+                None => break None,
+            }
+        },
+        Some(false) => loop {
+            threshold -= 1;
+            match search(threshold)? {
+                Some(true) => break Some(threshold),
+                Some(false) => {}
+                None => break None,
+            }
+        },
+        None => None,
+    };
+    let outcome = match margin {
+        None => Outcome::Unknown,
+        Some(margin) if margin > 0 => Outcome::One,
+        Some(margin) if margin < 0 => Outcome::Two,
+        Some(_) => Outcome::Draw,
+    };
+    Ok(Solution {
+        outcome,
+        nodes,
+        chances: None,
+        margin,
+    })
+}
+
+fn tree_search(mut board: Board, engine: Engine, limits: Limits) -> Solution {
+    let mut tree = Tree::new(&board.grid);
+    for (cord, tile) in &board.field {
+        if *tile != Tile::Empty {
+            tree.legal.insert(*cord, false);
+        }
+    }
+    tree.limits = limits;
+    let maximizer = board.player_one;
+    let value = match engine {
+        Engine::Alphabeta => tree.alphabeta(&mut board, maximizer, -2, 2),
+        _ => tree.minimax(&mut board, maximizer, 0),
+    };
+    let outcome = match (tree.aborted, value) {
+        (true, _) => Outcome::Unknown,
+        (_, 1) => Outcome::One,
+        (_, -1) => Outcome::Two,
+        _ => Outcome::Draw,
+    };
+    Solution {
+        outcome,
+        nodes: tree.nodes,
+        chances: None,
+        margin: None,
+    }
+}
+
+fn chance_search(mut board: Board, limits: Limits) -> Solution {
+    let mut search = Expectimax::new();
+    search.limits = limits;
+    let chances = search.value(&mut board);
+    let outcome = match chances {
+        _ if search.aborted => Outcome::Unknown,
+        Chances { one, .. } if one == Probability::ONE => Outcome::One,
+        Chances { two, .. } if two == Probability::ONE => Outcome::Two,
+        Chances { one, two } if one == Probability::ZERO && two == Probability::ZERO => {
+            Outcome::Draw
+        }
+        _ => Outcome::Chance,
+    };
+    Solution {
+        outcome,
+        nodes: search.nodes,
+        chances: (!search.aborted).then_some(chances),
+        margin: None,
+    }
 }
 
 pub fn _play(
-    size: i32,
-    shape1: &mut Vec<(i32, i32)>,
-    shape2: &mut Vec<(i32, i32)>,
-) -> Result<(), _Error> {
-    if size < 0 {
-        return Err(_Error::IllegalSize);
-    }
-    let mut board = Board::setup(size, shape1, shape2);
-    println!("");
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
+    moves_made: Vec<(i32, i32)>,
+) -> Result<(), Error> {
+    let mut board = opening(grid, shapes1, shapes2, rules, &moves_made)?;
+    println!();
     while !board.is_over() {
-        println!("{}", board.is_over());
+        if rules.turns == Turns::Random {
+            // every call draws fresh keys, which makes for a fair coin
+            board.player_one = RandomState::new().hash_one(()).is_multiple_of(2);
+        }
         let announce = format!(
             "\nIt is player {}'s turn to move: \n
 ---------------------------------\n",
-            board.player_to_move().to_string()
+            board.player_to_move()
         );
         println!("{announce}");
-        println!("{}", board.to_string());
+        println!("{}", board);
+        if rules.captures > 0 {
+            println!(
+                "Captured pairs: player 1 has {}, player 2 has {} of {}.\n",
+                board.captures(Tile::One),
+                board.captures(Tile::Two),
+                rules.captures
+            );
+        }
+        // Picker offers two cells, Chooser answers with one of them
+        let offering = rules.mode.offers() && board.offered.is_none();
+        match (offering, board.offered) {
+            (true, _) => println!("Offer two cells, or the last one twice.\n"),
+            (false, Some((a, b))) => {
+                let input = |cell| {
+                    let (x, y, z) = grid.lattice.coordinates(cell);
+                    match grid.lattice {
+                        Lattice::Cube => format!("{x} {y} {z}"),
+                        _ => format!("{x} {y}"),
+                    }
+                };
+                println!("Choose {} or {}.\n", input(a), input(b))
+            }
+            _ => {}
+        }
+        // the opening may leave a decision to the player to move
+        let choices: Vec<(&str, Move)> = board
+            .choices()
+            .into_iter()
+            .map(|choice| match choice {
+                Move::Swap => ("swap", choice),
+                Move::Propose => ("propose", choice),
+                _ => ("keep", choice),
+            })
+            .collect();
+        if !choices.is_empty() {
+            let words: Vec<&str> = choices.iter().map(|(word, _)| *word).collect();
+            println!("You may also answer {}.\n", words.join(" or "));
+        }
         let mut clean = false;
-        let mut x = -1;
-        let mut y = -1;
+        let mut cells = vec![];
+        let mut decision = None;
         while !clean {
             let mut input = String::new();
             while io::stdin().read_line(&mut input).is_err() {
                 println!("\nIOError occurred! Please try different input:\n");
             }
-            let trimmed: Vec<&str> = input.trim().split_whitespace().collect();
-            if trimmed.len() != 2 {
+            if input.is_empty() {
+                // stdin was closed, there is nobody left to play
+                return Ok(());
+            }
+            // cube cells take a layer as third coordinate
+            let per_cell = if grid.lattice == Lattice::Cube { 3 } else { 2 };
+            let expected = if offering { 2 * per_cell } else { per_cell };
+            let trimmed: Vec<&str> = input.split_whitespace().collect();
+            if let Some((_, choice)) = choices.iter().find(|(word, _)| trimmed == [*word]) {
+                decision = Some(*choice);
+                break;
+            }
+            if trimmed.len() != expected {
                 println!(
-                    "\nNumber of arguments incorrect. Expected 2, found {}. Please try different input:\n",
+                    "\nNumber of arguments incorrect. Expected {expected}, found {}. Please try different input:\n",
                     trimmed.len()
                 );
                 continue;
             }
-            let attempt: Vec<Result<i32, ParseIntError>> = trimmed
-                .iter()
-                .map(|str| i32::from_str_radix(&str, 10))
-                .collect();
+            let attempt: Vec<Result<i32, ParseIntError>> =
+                trimmed.iter().map(|str| str.parse::<i32>()).collect();
             if attempt
                 .iter()
                 .any(|res: &Result<i32, ParseIntError>| res.is_err())
@@ -90,67 +560,179 @@ pub fn _play(
                 continue;
             }
             let numbered: Vec<i32> = attempt.iter().map(|res| *res.as_ref().unwrap()).collect();
-            if numbered.iter().any(|num| num >= &size) {
-                println!("\nSize too large, please chose smaller coordinates! Please try different input:\n");
+            if numbered.iter().any(|c| c.abs() >= LAYER / 2) {
+                println!("\nInput is too far off the board. Please try different input:\n");
                 continue;
             }
-            x = numbered[0];
-            y = numbered[1];
+            cells = numbered
+                .chunks(per_cell)
+                .map(|c| {
+                    grid.lattice
+                        .cell((c[0], c[1], c.get(2).copied().unwrap_or(0)))
+                })
+                .collect();
             clean = true;
         }
-        // let place = board._place_play(x, y);
-        board.place_proof(x, y);
-        // if place.is_err() {
-        //     println!(
-        //         "\nUnexpected Error occurred: {:?}. Please try different input:\n",
-        //         place
-        //     );
-        //     continue;
-        // }
+        if let Some(choice) = decision {
+            board.play(choice);
+            continue;
+        }
+        let place = match cells[..] {
+            [a, b] => board._place_offer(a, b),
+            _ => board._place_play(cells[0].0, cells[0].1),
+        };
+        if let Err(game::_Error::_Forbidden) = place {
+            println!("\nBlack may not play an overline, double-four or double-three. Please try different input:\n");
+            continue;
+        }
+        if let Err(game::_Error::_Opening) = place {
+            println!("\nThe opening does not allow a stone there. Please try different input:\n");
+            continue;
+        }
+        if let Err(game::_Error::_Floating) = place {
+            println!("\nStones drop to the lowest empty cell of their column. Please try different input:\n");
+            continue;
+        }
+        if place.is_err() {
+            println!(
+                "\nUnexpected Error occurred: {:?}. Please try different input:\n",
+                place
+            );
+            continue;
+        }
     }
     match board.winner() {
         Tile::Empty => println!("\nThe game ended a draw.\n"),
         Tile::One => println!("\nPlayer one wins!\n"),
         Tile::Two => println!("\nPlayer two wins!\n"),
     }
-    println!("{}", board.to_string());
+    println!("{}", board);
     Ok(())
 }
 
 pub fn _simulate_minmax(
-    size: i32,
-    shape1: &mut Vec<(i32, i32)>,
-    shape2: &mut Vec<(i32, i32)>,
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
 ) -> &'static str {
-    let mut board = Board::setup(size, shape1, shape2);
-    board.place_proof((size / 2) as i32, (size / 2) as i32);
-    let mut tree = Tree::new(size);
-    tree.legal
-        .insert(((size / 2) as i32, (size / 2) as i32), false);
-    let value = tree._minimax(&mut board, true, 0);
+    let rules = Rules {
+        opening: Opening::Centre,
+        ..rules
+    };
+    let Ok(mut board) = Board::setup(grid, shapes1, shapes2, rules) else {
+        return "Illegal shape";
+    };
+    let mut tree = Tree::new(grid);
+    let maximizer = board.player_one;
+    let value = tree.minimax(&mut board, maximizer, 0);
     match value {
         1 => "Player one wins.",
-        2 => "Player two wins.",
+        -1 => "Player two wins.",
         0 => "Draw",
         _ => "",
     }
 }
 
 pub fn _simulate_alphabeta(
-    size: i32,
-    shape1: &mut Vec<(i32, i32)>,
-    shape2: &mut Vec<(i32, i32)>,
+    grid: &Grid,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    rules: Rules,
 ) -> &'static str {
-    let mut board = Board::setup(size, shape1, shape2);
-    board.place_proof((size / 2) as i32, (size / 2) as i32);
-    let mut tree = Tree::new(size);
-    tree.legal
-        .insert(((size / 2) as i32, (size / 2) as i32), false);
-    let value = tree._alphabeta(&mut board, true, -2, 2);
+    let rules = Rules {
+        opening: Opening::Centre,
+        ..rules
+    };
+    let Ok(mut board) = Board::setup(grid, shapes1, shapes2, rules) else {
+        return "Illegal shape";
+    };
+    let mut tree = Tree::new(grid);
+    let maximizer = board.player_one;
+    let value = tree.alphabeta(&mut board, maximizer, -2, 2);
     match value {
         1 => "Player one wins.",
-        2 => "Player two wins.",
+        -1 => "Player two wins.",
         0 => "Draw",
         _ => "wat",
     }
 }
+
+impl From<Tile> for Outcome {
+    fn from(tile: Tile) -> Self {
+        match tile {
+            Tile::One => Outcome::One,
+            Tile::Two => Outcome::Two,
+            Tile::Empty => Outcome::Draw,
+        }
+    }
+}
+
+impl From<ShapeError> for Error {
+    fn from(err: ShapeError) -> Self {
+        Error::Shape(err)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Outcome::One => "Player 1 wins",
+            Outcome::Two => "Player 2 wins",
+            Outcome::Draw => "Draw",
+            Outcome::Chance => "Up to chance",
+            Outcome::Unknown => "Unknown",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pns" => Ok(Engine::Pns),
+            "minimax" => Ok(Engine::Minimax),
+            "alphabeta" => Ok(Engine::Alphabeta),
+            _ => Err(format!(
+                "unknown engine '{s}', expected one of pns, minimax, alphabeta"
+            )),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::IllegalSize => write!(f, "the board needs at least one playable cell"),
+            Error::MismatchedSeam => write!(
+                f,
+                "a triangle board can only wrap around an even number of cells and a cube board not at all"
+            ),
+            Error::CordOffBoard((x, y)) => write!(f, "move {x},{y} is not on the board"),
+            Error::CordOccupied((x, y)) => write!(f, "move {x},{y} is already occupied"),
+            Error::CordBlocked((x, y)) => write!(f, "move {x},{y} is on a blocked cell"),
+            Error::NotOffered((x, y)) => write!(f, "move {x},{y} was not offered"),
+            Error::Forbidden((x, y)) => write!(
+                f,
+                "move {x},{y} is forbidden to Black, an overline, double-four or double-three"
+            ),
+            Error::Floating((x, y)) => write!(
+                f,
+                "move {x},{y} is above an empty cell, stones drop to the lowest empty cell of their column"
+            ),
+            Error::Opening((x, y)) => write!(f, "move {x},{y} is not allowed by the opening"),
+            Error::IllegalOffer((x, y), (u, v)) => write!(
+                f,
+                "cells {x},{y} and {u},{v} cannot be offered, an offer is two different cells unless only one is left"
+            ),
+            Error::OfferToMake => write!(
+                f,
+                "Picker has to offer two cells, analyze a position where Chooser picks one of them"
+            ),
+            Error::GameAlreadyOver => write!(f, "the game is already over"),
+            Error::Shape(err) => write!(f, "{err}"),
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use super::game::{Board, Shape, ShapeError, Tile};
+use super::grid::{Grid, Lattice, Topology};
+use super::polyomino::normalise;
+use super::rules::Rules;
+use std::fmt::Display;
+
+/// Rows of a text grid may be separated by newlines or by `/`.
+const ROW_SEPARATORS: [char; 2] = ['\n', '/'];
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    UnknownCell {
+        row: usize,
+        column: usize,
+        found: char,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    Shape(ShapeError),
+    StoneCount {
+        one: usize,
+        two: usize,
+    },
+    PlayedAfterWin(Tile),
+    BothWon,
+    /// A stone above an empty cell under gravity.
+    Floating {
+        row: usize,
+        column: usize,
+    },
+    VerticalWrap,
+    RaggedLayer {
+        layer: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Reads a shape such as `##.\n.##`, where `#`, `X`, `O` and `*` mark cells
+/// and `.` or spaces are left out. The cells are translated to the origin.
+/// Hex and triangle shapes are drawn like their boards, with spaces ignored
+/// and `.` for the gaps, so `# #\n # .` is the hex triangle. Triangles may
+/// also be drawn as `▲` or `▼` with `△` or `▽` for the gaps, and point the
+/// way their coordinates say regardless. Polycubes are drawn one layer after
+/// the other, separated by empty rows, so `##//#.` is the tripod.
+pub fn parse_shape_cells(text: &str, lattice: Lattice) -> Result<Vec<(i32, i32)>, ParseError> {
+    let mut cells = vec![];
+    let (mut y, mut z) = (0, 0);
+    for row in text.split(ROW_SEPARATORS) {
+        if lattice == Lattice::Cube && row.trim().is_empty() {
+            if y > 0 {
+                (y, z) = (0, z + 1);
+            }
+            continue;
+        }
+        let row: String = match lattice {
+            Lattice::Square | Lattice::Cube => row.trim_end().to_string(),
+            Lattice::Hex | Lattice::Triangle => {
+                row.chars().filter(|c| !c.is_whitespace()).collect()
+            }
+        };
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '#' | 'X' | 'x' | 'O' | 'o' | 'ⵔ' | '*' | '▲' | '▼' => {
+                    cells.push(lattice.cell((x as i32, y, z)))
+                }
+                '.' | ' ' | '-' | '—' | '△' | '▽' => continue,
+                found => {
+                    return Err(ParseError::UnknownCell {
+                        row: y as usize,
+                        column: x,
+                        found,
+                    })
+                }
+            }
+        }
+        y += 1;
+    }
+    let shape = Shape::new(cells, lattice).map_err(ParseError::Shape)?;
+    Ok(shape.cells().to_vec())
+}
+
+/// Draws shape cells as rows of `#` and `.`, or of filled and hollow
+/// triangles, the inverse of `parse_shape_cells`.
+pub fn shape_to_string(shape: &[(i32, i32)], lattice: Lattice) -> String {
+    let shape: Vec<(i32, i32, i32)> = normalise(shape.to_vec(), lattice)
+        .into_iter()
+        .map(|cell| lattice.coordinates(cell))
+        .collect();
+    let width = shape.iter().map(|(x, _, _)| *x).max().unwrap_or(-1) + 1;
+    let height = shape.iter().map(|(_, y, _)| *y).max().unwrap_or(-1) + 1;
+    let depth = shape.iter().map(|(_, _, z)| *z).max().unwrap_or(0) + 1;
+    let mut string = String::new();
+    for z in 0..depth {
+        if z > 0 {
+            string.push('\n');
+        }
+        for y in 0..height {
+            if lattice == Lattice::Hex {
+                string.push_str(&" ".repeat(y as usize));
+            }
+            for x in 0..width {
+                let up = (x + y) % 2 == 0;
+                string.push(match (lattice, shape.contains(&(x, y, z)), up) {
+                    (Lattice::Triangle, true, true) => '▲',
+                    (Lattice::Triangle, true, false) => '▼',
+                    (Lattice::Triangle, false, true) => '△',
+                    (Lattice::Triangle, false, false) => '▽',
+                    (_, true, _) => '#',
+                    (_, false, _) => '.',
+                });
+                if matches!(lattice, Lattice::Hex | Lattice::Triangle) && x < width - 1 {
+                    string.push(' ');
+                }
+            }
+            string.push('\n');
+        }
+    }
+    string
+}
+
+/// Reads a position such as `.X.\nO..\n..#`, where `O` (or `ⵔ`) are the
+/// stones of player one, `X` those of player two, `.` (or `—`, `△`, `▽`) empty cells
+/// and `#` cells that are not part of the board. Edges marked with `~`, a
+/// column on both sides or a row above and below, wrap around. Spaces are
+/// ignored, so the `Display` output of `Board` reads back, and on the hex
+/// lattice the columns are axial coordinates however far rows are indented.
+/// Cube boards list their layers in turn, separated by empty rows.
+pub fn parse_board(
+    text: &str,
+    shapes1: &[Vec<(i32, i32)>],
+    shapes2: &[Vec<(i32, i32)>],
+    lattice: Lattice,
+    rules: Rules,
+) -> Result<Board, ParseError> {
+    let mut layers: Vec<Vec<Vec<Option<Tile>>>> = vec![vec![]];
+    let (mut wraps_x, mut wraps_y) = (false, false);
+    for row in text.split(ROW_SEPARATORS) {
+        let row: String = row.chars().filter(|c| !c.is_whitespace()).collect();
+        let rows = layers.last_mut().expect("There is always a layer.");
+        if row.is_empty() {
+            if lattice == Lattice::Cube && !rows.is_empty() {
+                layers.push(vec![]);
+            }
+            continue;
+        }
+        let y = rows.len();
+        if row.chars().all(|c| c == '~') {
+            wraps_y = true;
+            continue;
+        }
+        let row = match row.strip_prefix('~').and_then(|row| row.strip_suffix('~')) {
+            Some(inner) => {
+                wraps_x = true;
+                inner
+            }
+            None => &row,
+        };
+        let mut tiles = vec![];
+        for (x, c) in row.chars().enumerate() {
+            tiles.push(match c {
+                'O' | 'o' | 'ⵔ' | '0' => Some(Tile::One),
+                'X' | 'x' => Some(Tile::Two),
+                '.' | '-' | '—' | '_' | '△' | '▽' | '·' => Some(Tile::Empty),
+                '#' => None,
+                found => {
+                    return Err(ParseError::UnknownCell {
+                        row: y,
+                        column: x,
+                        found,
+                    })
+                }
+            });
+        }
+        if let Some(first) = layers[0].first() {
+            if first.len() != tiles.len() {
+                return Err(ParseError::RaggedRow {
+                    row: y,
+                    expected: first.len(),
+                    found: tiles.len(),
+                });
+            }
+        }
+        layers
+            .last_mut()
+            .expect("There is always a layer.")
+            .push(tiles);
+    }
+    if layers.last().is_some_and(|rows| rows.is_empty()) {
+        layers.pop();
+    }
+    if layers.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    for (z, rows) in layers.iter().enumerate() {
+        if rows.len() != layers[0].len() {
+            return Err(ParseError::RaggedLayer {
+                layer: z,
+                expected: layers[0].len(),
+                found: rows.len(),
+            });
+        }
+    }
+    let mut tiles = vec![];
+    for (z, rows) in layers.iter().enumerate() {
+        for (y, row) in rows.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                tiles.push((lattice.cell((x as i32, y as i32, z as i32)), *tile));
+            }
+        }
+    }
+    let topology = match (wraps_x, wraps_y) {
+        (false, false) => Topology::Plane,
+        (true, false) => Topology::Cylinder,
+        (true, true) => Topology::Torus,
+        (false, true) => return Err(ParseError::VerticalWrap),
+    };
+    let grid = Grid {
+        depth: layers.len() as i32,
+        topology,
+        lattice,
+        ..Grid::rectangle(layers[0][0].len() as i32, layers[0].len() as i32)
+    }
+    .with_blocked(
+        tiles
+            .iter()
+            .filter(|(_, tile)| tile.is_none())
+            .map(|(cell, _)| *cell),
+    );
+    if grid.cells().is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut board = Board::setup(&grid, shapes1, shapes2, rules).map_err(ParseError::Shape)?;
+    for (cell, tile) in tiles {
+        if let Some(tile) = tile {
+            board.put(cell, tile);
+        }
+    }
+    if let Some(((x, y), _)) = board
+        .field
+        .iter()
+        .find(|(cell, tile)| **tile != Tile::Empty && !board.supported(**cell))
+    {
+        return Err(ParseError::Floating {
+            row: *y as usize,
+            column: *x as usize,
+        });
+    }
+    let count = |tile: Tile| board.field.values().filter(|t| **t == tile).count();
+    let (one, two) = (count(Tile::One), count(Tile::Two));
+    board.turn = one + two;
+    // offers hand out a stone to each player, and the last cell to Chooser
+    let reachable = match rules.mode.offers() {
+        true => one == two || (two == one + 1 && board.draw()),
+        false => rules.schedule.counts(one + two) == (one, two),
+    };
+    if !reachable {
+        return Err(ParseError::StoneCount { one, two });
+    }
+    board.player_one = board.decider() == Tile::One || rules.mode.offers();
+    // the stone that formed a shape was the last one placed, offers place
+    // two at once
+    let last = match rules.mode.offers() {
+        true => None,
+        false => Some(rules.schedule.mover(board.turn.saturating_sub(1))),
+    };
+    let formed = |tile: Tile| {
+        board
+            .field
+            .iter()
+            .any(|((x, y), t)| *t == tile && board.forms_shape(*x, *y, tile))
+            && rules.mode.builds(tile)
+    };
+    let (one_formed, two_formed) = (formed(Tile::One), formed(Tile::Two));
+    let former = match (one_formed, two_formed) {
+        (true, true) => return Err(ParseError::BothWon),
+        (true, false) if last.is_some_and(|last| last != Tile::One) => {
+            return Err(ParseError::PlayedAfterWin(Tile::One))
+        }
+        (false, true) if last.is_some_and(|last| last != Tile::Two) => {
+            return Err(ParseError::PlayedAfterWin(Tile::Two))
+        }
+        (true, false) => Tile::One,
+        (false, true) => Tile::Two,
+        (false, false) => Tile::Empty,
+    };
+    if rules.goal.scores() {
+        board.game_over = board.draw();
+        board.winner = board.leader();
+        return Ok(board);
+    }
+    board.game_over = one_formed || two_formed || board.draw();
+    board.winner = match (former, rules.mode.breaker()) {
+        (Tile::Empty, Some(breaker)) if board.game_over => rules.goal.winner(breaker),
+        _ => rules.goal.winner(former),
+    };
+    Ok(board)
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "the grid contains no cells"),
+            ParseError::UnknownCell { row, column, found } => {
+                write!(f, "unexpected '{found}' in row {row}, column {column}")
+            }
+            ParseError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {row} has {found} cells, expected {expected}"),
+            ParseError::Shape(err) => write!(f, "{err}"),
+            ParseError::StoneCount { one, two } => write!(
+                f,
+                "player one has {one} stones and player two {two}, which no game can reach"
+            ),
+            ParseError::PlayedAfterWin(tile) => {
+                write!(
+                    f,
+                    "play continued after player {tile} had formed their shape"
+                )
+            }
+            ParseError::BothWon => write!(f, "both players have formed their shape"),
+            ParseError::Floating { row, column } => write!(
+                f,
+                "the stone in row {row}, column {column} is above an empty cell, which gravity does not allow"
+            ),
+            ParseError::VerticalWrap => write!(
+                f,
+                "boards that wrap top to bottom have to wrap left to right as well"
+            ),
+            ParseError::RaggedLayer {
+                layer,
+                expected,
+                found,
+            } => write!(f, "layer {layer} has {found} rows, expected {expected}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gomoku::catalogue;
+    use crate::gomoku::game::Move;
+
+    #[test]
+    fn shapes_read_back() {
+        for lattice in [
+            Lattice::Square,
+            Lattice::Hex,
+            Lattice::Triangle,
+            Lattice::Cube,
+        ] {
+            for animal in catalogue::animals(lattice) {
+                let text = shape_to_string(animal.cells, lattice);
+                let cells = parse_shape_cells(&text, lattice).unwrap();
+                assert_eq!(cells, normalise(animal.shape(), lattice), "{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn boards_read_back() {
+        let square = Grid::rectangle(4, 3);
+        let boards = [
+            (square.clone(), vec![(0, 0), (1, 1), (3, 2)]),
+            (square.clone().with_blocked([(2, 0)]), vec![(0, 2), (3, 0)]),
+            (
+                Grid {
+                    topology: Topology::Torus,
+                    ..square.clone()
+                },
+                vec![(1, 0), (2, 2)],
+            ),
+            (
+                Grid {
+                    lattice: Lattice::Hex,
+                    ..square.clone()
+                },
+                vec![(3, 0), (0, 2), (1, 2)],
+            ),
+            (
+                Grid {
+                    lattice: Lattice::Triangle,
+                    ..square.clone()
+                },
+                vec![(1, 0), (2, 1)],
+            ),
+            (
+                Grid {
+                    depth: 2,
+                    lattice: Lattice::Cube,
+                    ..Grid::rectangle(2, 2)
+                },
+                vec![Lattice::Cube.cell((1, 0, 1)), (0, 1)],
+            ),
+        ];
+        // a bar too long for the boards, which nobody forms
+        let shape = [(0..5).map(|x| (x, 0)).collect::<Vec<_>>()];
+        for (grid, moves) in boards {
+            let mut board = Board::setup(&grid, &shape, &shape, Rules::default()).unwrap();
+            for (i, cell) in moves.into_iter().enumerate() {
+                let tile = if i % 2 == 0 { Tile::One } else { Tile::Two };
+                board.put(cell, tile);
+            }
+            let text = board.to_string();
+            let parsed = parse_board(&text, &shape, &shape, grid.lattice, Rules::default());
+            let parsed = parsed.unwrap_or_else(|err| panic!("{err}:\n{text}"));
+            assert_eq!(parsed.grid, grid, "{text}");
+            assert_eq!(parsed.field, board.field, "{text}");
+        }
+    }
+
+    #[test]
+    fn played_boards_read_back() {
+        let shape = [vec![(0, 0), (1, 0), (2, 0)]];
+        let mut board =
+            Board::setup(&Grid::rectangle(3, 3), &shape, &shape, Rules::default()).unwrap();
+        for cell in [(1, 1), (0, 0), (2, 2)] {
+            board.play(Move::Stone(cell));
+        }
+        let parsed = parse_board(
+            &board.to_string(),
+            &shape,
+            &shape,
+            Lattice::Square,
+            Rules::default(),
+        )
+        .unwrap();
+        assert_eq!(parsed.field, board.field);
+        assert_eq!(parsed.turn, board.turn);
+        assert_eq!(parsed.player_to_move(), board.player_to_move());
+        assert_eq!(parsed.canonical_hash(), board.canonical_hash());
+    }
+}
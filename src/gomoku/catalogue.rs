@@ -0,0 +1,643 @@
+use super::game::{Shape, Symmetry};
+use super::grid::{Lattice, LAYER};
+
+/// A named polyomino, polyhex, polyiamond or polycube, the animals of Harary's achievement games.
+#[derive(Debug, PartialEq)]
+pub struct Animal {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Cells translated to the origin, ready for `Shapes::new`.
+    pub cells: &'static [(i32, i32)],
+    pub lattice: Lattice,
+}
+
+impl Animal {
+    pub fn shape(&self) -> Vec<(i32, i32)> {
+        self.cells.to_vec()
+    }
+
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        self.to_shape().symmetry()
+    }
+
+    fn to_shape(&self) -> Shape {
+        Shape::new(self.shape(), self.lattice).expect("Catalogue shapes are connected.")
+    }
+}
+
+/// The named animals living on the lattice.
+pub fn animals(lattice: Lattice) -> &'static [Animal] {
+    match lattice {
+        Lattice::Square => &CATALOGUE,
+        Lattice::Hex => &POLYHEXES,
+        Lattice::Triangle => &POLYIAMONDS,
+        Lattice::Cube => &POLYCUBES,
+    }
+}
+
+/// Looks up an animal of the lattice by name or alias, ignoring case and
+/// `-`, `_` or space separators.
+pub fn lookup(name: &str, lattice: Lattice) -> Option<&'static Animal> {
+    let key = normalise(name);
+    animals(lattice).iter().find(|animal| {
+        normalise(animal.name) == key || animal.aliases.iter().any(|alias| normalise(alias) == key)
+    })
+}
+
+/// Finds the animal congruent to the shape under rotations and reflections.
+pub fn find(shape: &[(i32, i32)], lattice: Lattice) -> Option<&'static Animal> {
+    let shape = Shape::new(shape.to_vec(), lattice).ok()?;
+    animals(lattice)
+        .iter()
+        .find(|animal| animal.to_shape().is_congruent(&shape))
+}
+
+fn normalise(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// All free polyominoes up to size 6. The animals up to size four carry
+/// Harary's names, the pentominoes their usual letters and the hexominoes
+/// are numbered, except for Snaky.
+pub const CATALOGUE: [Animal; 56] = [
+    Animal {
+        name: "Tic",
+        aliases: &["monomino"],
+        cells: &[(0, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Domino",
+        aliases: &["stub"],
+        cells: &[(0, 0), (1, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "El",
+        aliases: &["tromino-l", "corner"],
+        cells: &[(0, 0), (1, 0), (1, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Tic-Tac-Toe",
+        aliases: &["tromino-i"],
+        cells: &[(0, 0), (1, 0), (2, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Elly",
+        aliases: &["tetromino-l"],
+        cells: &[(0, 0), (1, 0), (1, 1), (1, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Fatty",
+        aliases: &["tetromino-o", "block"],
+        cells: &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Knobby",
+        aliases: &["tetromino-t"],
+        cells: &[(0, 0), (1, 0), (2, 0), (1, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Skinny",
+        aliases: &["tetromino-i"],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Tippy",
+        aliases: &["tetromino-s", "tetromino-z"],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "F-pentomino",
+        aliases: &[],
+        cells: &[(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "I-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "L-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "N-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "P-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "T-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (1, 1), (1, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "U-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "V-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "W-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "X-pentomino",
+        aliases: &[],
+        cells: &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Y-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0), (1, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Z-pentomino",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-1",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-2",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (2, 0), (3, 0), (4, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-3",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 0), (3, 0), (4, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-4",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (2, 1), (3, 0), (4, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-5",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (3, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-6",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (2, 0), (2, 1), (3, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-7",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (2, 0), (3, 0), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-8",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 0), (2, 0), (3, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-9",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 0), (2, 1), (3, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-10",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 0), (3, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Snaky",
+        aliases: &["hexomino-11"],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1), (3, 1), (4, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-12",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-13",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (2, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-14",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-15",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (2, 0), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-16",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-17",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 0), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-18",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 1), (2, 0), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-19",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (0, 2), (1, 1), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-20",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (2, 1), (3, 1), (4, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-21",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-22",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (3, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-23",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 2), (3, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-24",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (1, 1), (1, 2), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-25",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (1, 1), (1, 2), (2, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-26",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-27",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-28",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (2, 1), (2, 2), (3, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-29",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 0), (2, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-30",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-31",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (3, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-32",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (2, 1), (2, 2), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-33",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (3, 2)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-34",
+        aliases: &[],
+        cells: &[(0, 1), (1, 0), (1, 1), (1, 2), (2, 1), (3, 1)],
+        lattice: Lattice::Square,
+    },
+    Animal {
+        name: "Hexomino-35",
+        aliases: &[],
+        cells: &[(0, 1), (1, 0), (1, 1), (2, 1), (2, 2), (3, 1)],
+        lattice: Lattice::Square,
+    },
+];
+
+/// All free polyhexes up to size four in axial coordinates, the tetrahexes
+/// under Gardner's names.
+pub const POLYHEXES: [Animal; 12] = [
+    Animal {
+        name: "Monohex",
+        aliases: &[],
+        cells: &[(0, 0)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Dihex",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Trihex-Bar",
+        aliases: &["trihex-i"],
+        cells: &[(0, 0), (1, 0), (2, 0)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Trihex-Chevron",
+        aliases: &["trihex-v"],
+        cells: &[(0, 0), (1, 0), (1, 1)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Trihex-Triangle",
+        aliases: &["trihex-o"],
+        cells: &[(0, 0), (0, 1), (1, 0)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Bar",
+        aliases: &["tetrahex-i"],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Pistol",
+        aliases: &["tetrahex-p"],
+        cells: &[(0, 0), (0, 1), (1, 0), (2, 0)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Worm",
+        aliases: &["tetrahex-j"],
+        cells: &[(0, 0), (1, 0), (2, 0), (2, 1)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Bee",
+        aliases: &["tetrahex-o"],
+        cells: &[(0, 0), (0, 1), (1, 0), (1, 1)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Wave",
+        aliases: &["tetrahex-s"],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Arch",
+        aliases: &["tetrahex-c"],
+        cells: &[(0, 0), (0, 2), (1, 0), (1, 1)],
+        lattice: Lattice::Hex,
+    },
+    Animal {
+        name: "Propeller",
+        aliases: &["tetrahex-y"],
+        cells: &[(0, 2), (1, 0), (1, 1), (2, 1)],
+        lattice: Lattice::Hex,
+    },
+];
+
+/// All free polyiamonds up to size four, whose cells point up when x + y is even.
+pub const POLYIAMONDS: [Animal; 6] = [
+    Animal {
+        name: "Moniamond",
+        aliases: &[],
+        cells: &[(0, 0)],
+        lattice: Lattice::Triangle,
+    },
+    Animal {
+        name: "Diamond",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0)],
+        lattice: Lattice::Triangle,
+    },
+    Animal {
+        name: "Triamond",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0)],
+        lattice: Lattice::Triangle,
+    },
+    Animal {
+        name: "Tetriamond-I",
+        aliases: &[],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0)],
+        lattice: Lattice::Triangle,
+    },
+    Animal {
+        name: "Tetriamond-C",
+        aliases: &[],
+        cells: &[(0, 0), (0, 1), (1, 0), (1, 1)],
+        lattice: Lattice::Triangle,
+    },
+    Animal {
+        name: "Tetriamond-T",
+        aliases: &[],
+        cells: &[(1, 0), (2, 0), (2, 1), (3, 0)],
+        lattice: Lattice::Triangle,
+    },
+];
+
+/// All free polycubes up to size four. The flat ones share the names of
+/// their polyominoes, the left and right screws are one shape as reflections
+/// count.
+pub const POLYCUBES: [Animal; 11] = [
+    Animal {
+        name: "Tic",
+        aliases: &["monocube"],
+        cells: &[(0, 0)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Domino",
+        aliases: &["dicube"],
+        cells: &[(0, 0), (1, 0)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "El",
+        aliases: &["tricube-l"],
+        cells: &[(0, 0), (1, 0), (1, 1)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Tic-Tac-Toe",
+        aliases: &["tricube-i"],
+        cells: &[(0, 0), (1, 0), (2, 0)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Elly",
+        aliases: &["tetracube-l"],
+        cells: &[(0, 0), (1, 0), (1, 1), (1, 2)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Fatty",
+        aliases: &["tetracube-o"],
+        cells: &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Knobby",
+        aliases: &["tetracube-t"],
+        cells: &[(0, 0), (1, 0), (2, 0), (1, 1)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Skinny",
+        aliases: &["tetracube-i"],
+        cells: &[(0, 0), (1, 0), (2, 0), (3, 0)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Tippy",
+        aliases: &["tetracube-s"],
+        cells: &[(0, 0), (1, 0), (1, 1), (2, 1)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Tripod",
+        aliases: &["branch"],
+        cells: &[(0, 0), (1, 0), (0, 1), (LAYER, 0)],
+        lattice: Lattice::Cube,
+    },
+    Animal {
+        name: "Screw",
+        aliases: &["tetracube-screw"],
+        cells: &[(0, 0), (1, 0), (1, 1), (1 + LAYER, 1)],
+        lattice: Lattice::Cube,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gomoku::polyomino::{canonical, enumerate, Equivalence};
+    use std::collections::BTreeSet;
+
+    /// Each lattice with the size of its largest catalogued animals.
+    const CATALOGUED: [(Lattice, usize); 4] = [
+        (Lattice::Square, 6),
+        (Lattice::Hex, 4),
+        (Lattice::Triangle, 4),
+        (Lattice::Cube, 4),
+    ];
+
+    #[test]
+    fn names_are_unique() {
+        for (lattice, _) in CATALOGUED {
+            let mut names = BTreeSet::new();
+            for animal in animals(lattice) {
+                for name in [animal.name].iter().chain(animal.aliases) {
+                    assert!(names.insert(normalise(name)), "{name} on {lattice}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_animal_is_named_once() {
+        for (lattice, largest) in CATALOGUED {
+            for animal in animals(lattice) {
+                assert_eq!(animal.lattice, lattice, "{}", animal.name);
+                assert!(animal.size() <= largest, "{}", animal.name);
+            }
+            for size in 1..=largest {
+                let mut named: Vec<_> = animals(lattice)
+                    .iter()
+                    .filter(|animal| animal.size() == size)
+                    .map(|animal| canonical(animal.cells, Equivalence::Free, lattice))
+                    .collect();
+                named.sort();
+                let free = enumerate(size, Equivalence::Free, lattice);
+                assert_eq!(named, free, "size {size} on {lattice}");
+            }
+        }
+    }
+}
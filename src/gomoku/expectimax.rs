@@ -0,0 +1,248 @@
+use super::game::*;
+use super::Limits;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Add;
+use std::time::Instant;
+
+/// The most coin flips a value can hold, each one halving the probabilities.
+const MAX_FLIPS: usize = 120;
+
+/// An exact probability m / 2^k. Every value of a random-turn game is one,
+/// being averages over fair coin flips of certain results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Probability {
+    numerator: u128,
+    exponent: u32,
+}
+
+/// How likely each player is to win, the rest being draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chances {
+    pub one: Probability,
+    pub two: Probability,
+}
+
+/// Solves random-turn games, where a fair coin decides who places each
+/// stone. Each player picks the stone that maximises their chance to win
+/// less that of their opponent, and the value of a position is the average
+/// over both outcomes of the coin flip.
+pub struct Expectimax {
+    /// Values of positions solved so far, shared between transpositions.
+    memo: HashMap<u64, Chances>,
+    pub limits: Limits,
+    pub nodes: usize,
+    pub aborted: bool,
+    start: Instant,
+}
+
+impl Probability {
+    pub const ZERO: Probability = Probability {
+        numerator: 0,
+        exponent: 0,
+    };
+    pub const ONE: Probability = Probability {
+        numerator: 1,
+        exponent: 0,
+    };
+
+    fn reduced(mut numerator: u128, mut exponent: u32) -> Self {
+        while exponent > 0 && numerator.is_multiple_of(2) {
+            numerator /= 2;
+            exponent -= 1;
+        }
+        Probability {
+            numerator,
+            exponent,
+        }
+    }
+
+    /// The numerators of both probabilities over their common denominator.
+    fn aligned(self, other: Self) -> (u128, u128, u32) {
+        let exponent = self.exponent.max(other.exponent);
+        (
+            self.numerator << (exponent - self.exponent),
+            other.numerator << (exponent - other.exponent),
+            exponent,
+        )
+    }
+
+    /// The mean of two probabilities, the value of a fair coin flip between them.
+    pub fn average(self, other: Self) -> Self {
+        let (a, b, exponent) = self.aligned(other);
+        Probability::reduced(a + b, exponent + 1)
+    }
+}
+
+impl Chances {
+    /// The chances once the game has been won by `winner`, a draw for an empty tile.
+    pub fn certain(winner: Tile) -> Self {
+        let (one, two) = match winner {
+            Tile::One => (Probability::ONE, Probability::ZERO),
+            Tile::Two => (Probability::ZERO, Probability::ONE),
+            Tile::Empty => (Probability::ZERO, Probability::ZERO),
+        };
+        Chances { one, two }
+    }
+
+    fn average(self, other: Self) -> Self {
+        Chances {
+            one: self.one.average(other.one),
+            two: self.two.average(other.two),
+        }
+    }
+
+    /// Whether `tile` would rather play into these chances than into
+    /// `other`: those with the larger lead, of them the larger chance to win.
+    fn better_for(&self, tile: Tile, other: &Chances) -> bool {
+        let (mine, theirs, other_mine, other_theirs) = match tile {
+            Tile::One => (self.one, self.two, other.one, other.two),
+            _ => (self.two, self.one, other.two, other.one),
+        };
+        // compares mine - theirs with other_mine - other_theirs
+        match (mine + other_theirs).cmp(&(other_mine + theirs)) {
+            Ordering::Equal => mine > other_mine,
+            ordering => ordering == Ordering::Greater,
+        }
+    }
+}
+
+impl Expectimax {
+    pub fn new() -> Self {
+        Expectimax {
+            memo: HashMap::new(),
+            limits: Limits::default(),
+            nodes: 0,
+            aborted: false,
+            start: Instant::now(),
+        }
+    }
+
+    /// Counts a visited node and reports whether the search has to stop.
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if self.limits.exceeded(self.nodes, self.start) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    /// The chances of both players before the coin decides who moves next.
+    pub fn value(&mut self, board: &mut Board) -> Chances {
+        if board.is_over() {
+            return Chances::certain(*board.winner());
+        }
+        if let Some(winner) = board.settled() {
+            return Chances::certain(winner);
+        }
+        let hash = board.canonical_hash();
+        if let Some(known) = self.memo.get(&hash) {
+            return *known;
+        }
+        let empty: Vec<(i32, i32)> = board
+            .field
+            .iter()
+            .filter(|(_, tile)| **tile == Tile::Empty)
+            .map(|(cell, _)| *cell)
+            .collect();
+        if empty.len() > MAX_FLIPS {
+            self.aborted = true;
+        }
+        if self.visit() {
+            return Chances::certain(Tile::Empty);
+        }
+        let mover = board.player_one;
+        let mut best = |tile: Tile| {
+            let mut best: Option<Chances> = None;
+            // the coin hands the stone to `tile`, whose legal moves count
+            board.player_one = tile == Tile::One;
+            let legal: Vec<(i32, i32)> = empty
+                .iter()
+                .copied()
+                .filter(|cell| board.legal(*cell))
+                .collect();
+            for cell in legal {
+                board.place_as(cell, tile);
+                let chances = self.value(board);
+                board.undo(cell.0, cell.1);
+                if best.is_none_or(|best| chances.better_for(tile, &best)) {
+                    best = Some(chances);
+                }
+            }
+            // Black in Renju may be left without a move, which ends in a draw
+            best.unwrap_or(Chances::certain(Tile::Empty))
+        };
+        let value = best(Tile::One).average(best(Tile::Two));
+        board.player_one = mover;
+        if !self.aborted {
+            self.memo.insert(hash, value);
+        }
+        value
+    }
+}
+
+impl Add for Probability {
+    type Output = Probability;
+
+    fn add(self, other: Self) -> Self {
+        let (a, b, exponent) = self.aligned(other);
+        Probability::reduced(a + b, exponent)
+    }
+}
+
+impl Ord for Probability {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, _) = self.aligned(*other);
+        a.cmp(&b)
+    }
+}
+
+impl PartialOrd for Probability {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Writes the probability as a reduced fraction, or 0 and 1.
+impl Display for Probability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.exponent {
+            0 => write!(f, "{}", self.numerator),
+            exponent => write!(f, "{}/{}", self.numerator, 1u128 << exponent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gomoku::grid::Grid;
+    use crate::gomoku::rules::{Placement, Rules, Turns};
+
+    fn chances(placement: Placement) -> Chances {
+        let rules = Rules {
+            turns: Turns::Random,
+            placement,
+            ..Rules::default()
+        };
+        let bar = [vec![(0, 0), (1, 0), (2, 0)]];
+        let mut board = Board::setup(&Grid::rectangle(3, 2), &bar, &bar, rules).unwrap();
+        Expectimax::new().value(&mut board)
+    }
+
+    #[test]
+    fn random_turns_under_gravity() {
+        let chance = |numerator, exponent| Probability {
+            numerator,
+            exponent,
+        };
+        // gravity keeps each top cell out of reach until the one below is taken
+        let gravity = chances(Placement::Gravity);
+        assert_eq!(gravity.one, chance(7, 5));
+        assert_eq!(gravity.two, chance(7, 5));
+        let free = chances(Placement::Free);
+        assert_eq!(free.one, chance(5, 4));
+        assert_eq!(free.two, chance(5, 4));
+    }
+}
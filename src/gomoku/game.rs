@@ -1,9 +1,18 @@
-#![allow(unused)]
-use std::{collections::BTreeMap, fmt::Display, result::Result};
+use super::grid::{Grid, Lattice};
+use super::polyomino::{canonical, normalise, Equivalence};
+use super::renju;
+use super::rules::{Goal, Opening, Placement, Rules, Schedule};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    result::Result,
+};
 
 #[derive(Debug, Hash)]
 pub struct Board {
-    pub size: i32,
+    pub grid: Grid,
     pub turn: usize,
     pub field: BTreeMap<(i32, i32), Tile>,
     pub player_one: bool,
@@ -11,20 +20,111 @@ pub struct Board {
     pub shapes1: Shapes,
     pub shapes2: Shapes,
     pub winner: Tile,
+    pub rules: Rules,
+    /// The cells Picker offers to Chooser, while Chooser has to take one.
+    pub offered: Option<((i32, i32), (i32, i32))>,
+    /// Offers already taken up, in order, to undo the choices.
+    offers: Vec<((i32, i32), (i32, i32))>,
+    placements1: Placements,
+    placements2: Placements,
+    /// The pairs each player has captured when the rules play captures.
+    captures1: usize,
+    captures2: usize,
+    /// The stones each placed stone captured, in order, to undo the captures.
+    taken: Vec<Vec<(i32, i32)>>,
+    /// The decisions of the opening taken so far, in order.
+    decisions: Vec<Move>,
+    /// Board symmetries as permutations of the cells in `field` order, the
+    /// identity first.
+    symmetries: Vec<Vec<usize>>,
+    /// The position of each cell in `field` order.
+    index: BTreeMap<(i32, i32), usize>,
+    /// The Zobrist hash of the stones of each image under the symmetries,
+    /// kept in step with every stone put on or taken off the board.
+    zobrist: Vec<u64>,
+    /// The Zobrist hash of the pairs the players have captured.
+    captured: u64,
 }
 
 #[derive(Debug)]
 pub enum _Error {
     _CordIllegalLarge,
     _CordIllegalSmall,
+    _CordBlocked,
     _AlreadyOccupied,
+    _NotOffered,
+    _IllegalOffer,
+    _Forbidden,
+    _Floating,
+    _Opening,
 }
 
+/// A move of the player to move: a stone, or when turns are offers the two
+/// cells Picker offers, one of which Chooser then takes as a stone. A lone
+/// last cell is offered together with itself. Openings with swaps add the
+/// decisions to take over the other player's stones, to propose two more
+/// stones in Swap2 and to keep one's side after such a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Stone((i32, i32)),
+    Offer((i32, i32), (i32, i32)),
+    Swap,
+    Propose,
+    Keep,
+}
+
+/// The distinct normalised orientations of a player's shapes, any of
+/// which counts as formed.
 #[derive(Debug, Hash)]
 pub struct Shapes {
     shapes: Vec<Vec<(i32, i32)>>,
 }
 
+/// Every placement of a player's shapes on the grid, indexed by the cells it covers.
+#[derive(Debug, Hash)]
+pub struct Placements {
+    placements: Vec<Vec<(i32, i32)>>,
+    /// The cells continuing a straight line of each placement, which an
+    /// exact shape keeps free of its player's stones. Empty unless exact.
+    extensions: Vec<Vec<(i32, i32)>>,
+    by_cell: BTreeMap<(i32, i32), Vec<usize>>,
+}
+
+/// A placement with its extensions.
+pub type Extended<'a> = (&'a Vec<(i32, i32)>, &'a Vec<(i32, i32)>);
+
+/// A connected, non-empty set of cells translated to the origin.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shape {
+    cells: Vec<(i32, i32)>,
+    lattice: Lattice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeError {
+    Empty,
+    Disconnected,
+}
+
+/// Symmetry group of a shape: cyclic groups contain rotations only,
+/// dihedral groups reflections as well. The groups of order three and six
+/// only occur on the hex grid. Polycubes are only told apart by the order
+/// of their group, out of the 48 symmetries of the cube.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub enum Symmetry {
+    C1,
+    C2,
+    C3,
+    C4,
+    C6,
+    D1,
+    D2,
+    D3,
+    D4,
+    D6,
+    Spatial(usize),
+}
+
 #[derive(Debug, PartialEq, Clone, Copy, Hash)]
 pub enum Tile {
     One,
@@ -32,113 +132,894 @@ pub enum Tile {
     Empty,
 }
 impl Shapes {
-    pub fn new(shape: &mut Vec<(i32, i32)>) -> Self {
-        let shapes = get_shapes(shape);
-        Shapes { shapes }
+    pub fn new(bases: &[Vec<(i32, i32)>], lattice: Lattice) -> Result<Self, ShapeError> {
+        if bases.is_empty() {
+            return Err(ShapeError::Empty);
+        }
+        let mut shapes = vec![];
+        for base in bases {
+            let base = Shape::new(base.clone(), lattice)?;
+            for orientation in base.orientations() {
+                if !shapes.contains(&orientation) {
+                    shapes.push(orientation);
+                }
+            }
+        }
+        Ok(Shapes { shapes })
+    }
+
+    pub fn orientations(&self) -> &[Vec<(i32, i32)>] {
+        &self.shapes
+    }
+
+    /// The cells of the largest shape.
+    pub fn size(&self) -> usize {
+        self.shapes.iter().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+impl Placements {
+    pub fn new(grid: &Grid, shapes: &Shapes, exact: bool) -> Self {
+        let placements = grid.placements(shapes.orientations());
+        let extensions = placements
+            .iter()
+            .map(|placement| match exact {
+                true => extensions(grid, placement),
+                false => vec![],
+            })
+            .collect();
+        Placements::index(placements, extensions)
+    }
+
+    /// Every straight line of `length` cells along the directions of the
+    /// lattice, the placements of m,n,k-games. Exact lines keep the cells
+    /// right beyond both of their ends free.
+    pub fn lines(grid: &Grid, length: usize, exact: bool) -> Self {
+        let mut lines = BTreeMap::new();
+        for start in grid.cells() {
+            let (x, y, z) = grid.lattice.coordinates(start);
+            for (dx, dy, dz) in grid.lattice.directions() {
+                let at =
+                    |i: i32| grid.locate(grid.lattice.cell((x + i * dx, y + i * dy, z + i * dz)));
+                let Some(mut line) = (0..length as i32).map(at).collect::<Option<Vec<_>>>() else {
+                    continue;
+                };
+                line.sort();
+                line.dedup();
+                // lines that wrap onto themselves are left out
+                if line.len() < length {
+                    continue;
+                }
+                let ends = [at(-1), at(length as i32)]
+                    .into_iter()
+                    .flatten()
+                    .filter(|cell| exact && !line.contains(cell));
+                let mut ends: Vec<(i32, i32)> = ends.collect();
+                ends.dedup();
+                lines.entry(line).or_insert(ends);
+            }
+        }
+        let (placements, extensions) = lines.into_iter().unzip();
+        Placements::index(placements, extensions)
+    }
+
+    fn index(placements: Vec<Vec<(i32, i32)>>, extensions: Vec<Vec<(i32, i32)>>) -> Self {
+        let mut by_cell: BTreeMap<(i32, i32), Vec<usize>> = BTreeMap::new();
+        for (i, placement) in placements.iter().enumerate() {
+            for cell in placement {
+                by_cell.entry(*cell).or_default().push(i);
+            }
+        }
+        Placements {
+            placements,
+            extensions,
+            by_cell,
+        }
+    }
+
+    pub fn all(&self) -> &[Vec<(i32, i32)>] {
+        &self.placements
+    }
+
+    /// The placements with their extensions.
+    pub fn extended(&self) -> impl Iterator<Item = Extended<'_>> {
+        self.placements.iter().zip(&self.extensions)
+    }
+
+    /// The placements covering x, y, with their extensions.
+    pub fn through(&self, cell: (i32, i32)) -> impl Iterator<Item = Extended<'_>> {
+        self.by_cell
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .map(|i| (&self.placements[*i], &self.extensions[*i]))
+    }
+}
+
+impl Shape {
+    pub fn new(cells: Vec<(i32, i32)>, lattice: Lattice) -> Result<Self, ShapeError> {
+        let mut cells = normalise(cells, lattice);
+        cells.dedup();
+        if cells.is_empty() {
+            return Err(ShapeError::Empty);
+        }
+        if !is_connected(&cells, lattice) {
+            return Err(ShapeError::Disconnected);
+        }
+        Ok(Shape { cells, lattice })
+    }
+
+    pub fn cells(&self) -> &[(i32, i32)] {
+        &self.cells
+    }
+
+    /// The representative shared by all rotations and reflections of the shape.
+    pub fn canonical(&self) -> Shape {
+        Shape {
+            cells: canonical(&self.cells, Equivalence::Free, self.lattice),
+            lattice: self.lattice,
+        }
+    }
+
+    /// Whether a rotation or reflection turns one shape into the other.
+    pub fn is_congruent(&self, other: &Shape) -> bool {
+        self.canonical() == other.canonical()
+    }
+
+    /// The distinct orientations under the symmetries of the lattice, normalised.
+    pub fn orientations(&self) -> Vec<Vec<(i32, i32)>> {
+        let mut orientations: Vec<Vec<(i32, i32)>> = vec![];
+        for orientation in self.images() {
+            if !orientations.contains(&orientation) {
+                orientations.push(orientation);
+            }
+        }
+        orientations
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        let rotations = self.lattice.rotations();
+        let fixed: Vec<usize> = (0..rotations * 2)
+            .zip(self.images())
+            .filter(|(_, image)| *image == self.cells)
+            .map(|(i, _)| i)
+            .collect();
+        let reflects = fixed.iter().any(|i| *i >= rotations);
+        if self.lattice == Lattice::Cube {
+            return Symmetry::Spatial(fixed.len());
+        }
+        match (fixed.len(), reflects) {
+            (12, _) => Symmetry::D6,
+            (8, _) => Symmetry::D4,
+            (6, false) => Symmetry::C6,
+            (6, true) => Symmetry::D3,
+            (4, false) => Symmetry::C4,
+            (4, true) => Symmetry::D2,
+            (3, _) => Symmetry::C3,
+            (2, false) => Symmetry::C2,
+            (2, true) => Symmetry::D1,
+            _ => Symmetry::C1,
+        }
+    }
+
+    /// The shape under every symmetry of its lattice, normalised, in the
+    /// order of `Lattice::transforms`.
+    fn images(&self) -> Vec<Vec<(i32, i32)>> {
+        self.lattice
+            .transforms()
+            .iter()
+            .map(|t| {
+                let image = self
+                    .cells
+                    .iter()
+                    .map(|cell| self.lattice.transform(*t, *cell));
+                normalise(image.collect(), self.lattice)
+            })
+            .collect()
+    }
+}
+
+impl Move {
+    /// Reads a list of cells as moves. When turns are offers every three
+    /// cells are an offer and the cell Chooser takes from it, a trailing
+    /// pair is an offer still open and a trailing single cell is offered
+    /// on its own.
+    pub fn record(cells: &[(i32, i32)], offers: bool) -> Vec<Move> {
+        if !offers {
+            return cells.iter().copied().map(Move::Stone).collect();
+        }
+        let mut moves = vec![];
+        for turn in cells.chunks(3) {
+            match *turn {
+                [a] => moves.push(Move::Offer(a, a)),
+                [a, b] => moves.push(Move::Offer(a, b)),
+                [a, b, choice] => moves.extend([Move::Offer(a, b), Move::Stone(choice)]),
+                _ => unreachable!("Chunks hold one to three cells."),
+            }
+        }
+        moves
     }
 }
 
 impl Board {
     /// Setup function only to be called once
-    pub fn setup(size: i32, shape1: &mut Vec<(i32, i32)>, shape2: &mut Vec<(i32, i32)>) -> Board {
+    pub fn setup(
+        grid: &Grid,
+        shapes1: &[Vec<(i32, i32)>],
+        shapes2: &[Vec<(i32, i32)>],
+        rules: Rules,
+    ) -> Result<Board, ShapeError> {
         let mut field = BTreeMap::new();
-        for i in 0..size {
-            for j in 0..size {
-                field.insert((i as i32, j as i32), Tile::Empty);
-            }
+        for cell in grid.cells() {
+            field.insert(cell, Tile::Empty);
         }
-        Board {
-            size,
+        let shapes1 = Shapes::new(shapes1, grid.lattice)?;
+        let shapes2 = Shapes::new(shapes2, grid.lattice)?;
+        let placements = |shapes: &Shapes, tile: Tile| {
+            let exact = rules.exact.applies(tile);
+            match rules.lines {
+                true => Placements::lines(grid, shapes.size(), exact),
+                false => Placements::new(grid, shapes, exact),
+            }
+        };
+        let symmetries = symmetries(grid, rules.placement);
+        let index = field.keys().copied().zip(0..).collect();
+        Ok(Board {
+            grid: grid.clone(),
             turn: 0,
             field,
             player_one: true,
             game_over: false,
-            shapes1: Shapes::new(shape1),
-            shapes2: Shapes::new(shape2),
+            placements1: placements(&shapes1, Tile::One),
+            placements2: placements(&shapes2, Tile::Two),
+            shapes1,
+            shapes2,
             winner: Tile::Empty,
-        }
+            rules,
+            offered: None,
+            offers: vec![],
+            captures1: 0,
+            captures2: 0,
+            taken: vec![],
+            decisions: vec![],
+            index,
+            zobrist: vec![0; symmetries.len()],
+            symmetries,
+            captured: 0,
+        })
     }
 
     pub fn _reset(&mut self) {
-        for i in 0..self.size {
-            for j in 0..self.size {
-                self.field.insert((i, j), Tile::Empty);
-            }
+        for cell in self.grid.cells() {
+            self.put(cell, Tile::Empty);
+        }
+    }
+
+    /// Puts `tile` on the cell without playing a move, keeping the Zobrist
+    /// hashes in step, and gives back the tile that was there.
+    pub fn put(&mut self, cell: (i32, i32), tile: Tile) -> Option<Tile> {
+        let old = self.field.insert(cell, tile);
+        let index = self.index[&cell];
+        for (permutation, hash) in self.symmetries.iter().zip(&mut self.zobrist) {
+            let image = permutation[index];
+            *hash ^= stone_key(image, old.unwrap_or(Tile::Empty)) ^ stone_key(image, tile);
         }
+        old
+    }
+
+    /// Sets the pairs `tile` has captured, keeping their hash in step.
+    fn set_captures(&mut self, tile: Tile, count: usize) {
+        self.captured ^= captures_key(tile, self.captures(tile)) ^ captures_key(tile, count);
+        match tile {
+            Tile::One => self.captures1 = count,
+            Tile::Two => self.captures2 = count,
+            _ => panic!("Only players capture, empty tiles do not."),
+        }
+    }
+
+    /// Computes the Zobrist hashes afresh from the stones and the captures.
+    fn rehash(&mut self) {
+        let tiles: Vec<Tile> = self.field.values().copied().collect();
+        self.zobrist = self
+            .symmetries
+            .iter()
+            .map(|permutation| {
+                (tiles.iter().zip(permutation))
+                    .fold(0, |hash, (tile, image)| hash ^ stone_key(*image, *tile))
+            })
+            .collect();
+        self.captured =
+            captures_key(Tile::One, self.captures1) ^ captures_key(Tile::Two, self.captures2);
     }
 
     /// Places ones stone at coordinates x, y assuming it is still empty and on the board.
     pub fn _place_play(&mut self, x_cord: i32, y_cord: i32) -> Result<(), _Error> {
-        if x_cord >= self.size || y_cord >= self.size {
+        self._check(x_cord, y_cord)?;
+        if self.rules.mode.offers() {
+            return match self.offered {
+                Some((a, b)) if (x_cord, y_cord) == a || (x_cord, y_cord) == b => {
+                    self.place_proof(x_cord, y_cord);
+                    Ok(())
+                }
+                _ => Err(_Error::_NotOffered),
+            };
+        }
+        if !self.supported((x_cord, y_cord)) {
+            return Err(_Error::_Floating);
+        }
+        if !self.opens((x_cord, y_cord)) {
+            return Err(_Error::_Opening);
+        }
+        if self.forbidden((x_cord, y_cord)) {
+            return Err(_Error::_Forbidden);
+        }
+        self.place_proof(x_cord, y_cord);
+        Ok(())
+    }
+
+    /// Offers two empty cells to Chooser, the same one twice only if it is the last.
+    pub fn _place_offer(&mut self, a: (i32, i32), b: (i32, i32)) -> Result<(), _Error> {
+        self._check(a.0, a.1)?;
+        self._check(b.0, b.1)?;
+        let empty = self.empty();
+        if !self.rules.mode.offers() || self.offered.is_some() || (a == b && empty > 1) {
+            return Err(_Error::_IllegalOffer);
+        }
+        self.offer(a, b);
+        Ok(())
+    }
+
+    /// Whether the player to move may play the empty cell.
+    pub fn legal(&self, cell: (i32, i32)) -> bool {
+        self.supported(cell) && self.opens(cell) && !self.forbidden(cell)
+    }
+
+    /// Whether the opening lets the next stone go on the cell: the centre
+    /// first, in pro player one's second stone far enough from it, and no
+    /// stone at all while player one chooses a side in Swap2.
+    pub fn opens(&self, cell: (i32, i32)) -> bool {
+        match self.rules.opening {
+            Opening::Centre | Opening::Pro { .. } if self.turn == 0 => cell == self.grid.centre(),
+            Opening::Pro { distance } if self.turn == 2 => self.off_centre(cell) >= distance,
+            Opening::Swap2 => !self.choices().contains(&Move::Keep),
+            _ => true,
+        }
+    }
+
+    /// The rows or the columns between the cell and the centre, whichever
+    /// are more.
+    pub fn off_centre(&self, cell: (i32, i32)) -> i32 {
+        let (x, y, _) = self.grid.lattice.coordinates(cell);
+        let (u, v, _) = self.grid.lattice.coordinates(self.grid.centre());
+        (x - u).abs().max((y - v).abs())
+    }
+
+    /// The decisions of the opening open to the player to move: player two
+    /// may take over the first stone in swap, and the three first stones in
+    /// Swap2 or propose two more, after which player one picks a side.
+    pub fn choices(&self) -> Vec<Move> {
+        let undecided = self.decisions.is_empty();
+        match self.rules.opening {
+            Opening::Swap if self.turn == 1 && undecided => vec![Move::Swap],
+            Opening::Swap2 if self.turn == 3 && undecided => vec![Move::Swap, Move::Propose],
+            Opening::Swap2 if self.turn == 5 && self.decisions == [Move::Propose] => {
+                vec![Move::Swap, Move::Keep]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Takes a decision of the opening, a swap handing each player the
+    /// stones of the other.
+    fn decide(&mut self, decision: Move) {
+        self.decisions.push(decision);
+        if decision == Move::Swap {
+            self.recolour();
+        }
+        self.player_one = self.decider() == Tile::One;
+    }
+
+    /// Takes back the last decision of the opening.
+    fn retract(&mut self) {
+        if self.decisions.pop() == Some(Move::Swap) {
+            self.recolour();
+        }
+        self.player_one = self.decider() == Tile::One;
+    }
+
+    /// Swaps the stones, and the pairs they captured, between the players.
+    fn recolour(&mut self) {
+        for tile in self.field.values_mut() {
+            if *tile != Tile::Empty {
+                *tile = tile.opponent();
+            }
+        }
+        std::mem::swap(&mut self.captures1, &mut self.captures2);
+        self.rehash();
+    }
+
+    fn swapped(&self) -> bool {
+        self.decisions.contains(&Move::Swap)
+    }
+
+    /// Who moves next: the owner of the next stone by the schedule, which a
+    /// swap hands to the other player, except while Swap2 has a player
+    /// place stones of both or pick a side.
+    pub fn decider(&self) -> Tile {
+        let proposed = self.decisions == [Move::Propose];
+        match self.rules.opening {
+            Opening::Swap2 if self.turn < 3 => Tile::One,
+            Opening::Swap2 if proposed && self.turn < 5 => Tile::Two,
+            Opening::Swap2 if proposed => Tile::One,
+            _ => self.owner(),
+        }
+    }
+
+    /// The player the next stone belongs to by the schedule and the swaps.
+    fn owner(&self) -> Tile {
+        let colour = self.rules.schedule.mover(self.turn);
+        match self.swapped() {
+            true => colour.opponent(),
+            false => colour,
+        }
+    }
+
+    /// Whether a stone stays on the empty cell, which under gravity takes
+    /// the bottom row or a stone or blocked cell right below it.
+    pub fn supported(&self, (x, y): (i32, i32)) -> bool {
+        self.rules.placement == Placement::Free
+            || self
+                .field
+                .get(&(x, y + 1))
+                .is_none_or(|tile| *tile != Tile::Empty)
+    }
+
+    /// Whether the player to move is Black in Renju and may not play the empty cell.
+    pub fn forbidden(&self, cell: (i32, i32)) -> bool {
+        self.rules.renju && self.player_one && renju::forbidden(self, cell)
+    }
+
+    /// Whether `cell` is an empty cell of the board.
+    pub fn _is_open(&self, cell: (i32, i32)) -> bool {
+        self._check(cell.0, cell.1).is_ok()
+    }
+
+    /// Whether x, y is an empty cell of the board.
+    fn _check(&self, x_cord: i32, y_cord: i32) -> Result<(), _Error> {
+        let (x, y, z) = self.grid.lattice.coordinates((x_cord, y_cord));
+        if x >= self.grid.width || y >= self.grid.height || z >= self.grid.depth {
             return Err(_Error::_CordIllegalLarge);
         }
-        if x_cord < 0 || y_cord < 0 {
+        if x < 0 || y < 0 || z < 0 {
             return Err(_Error::_CordIllegalSmall);
         }
+        if !self.grid.contains((x_cord, y_cord)) {
+            return Err(_Error::_CordBlocked);
+        }
         if self.field.get(&(x_cord, y_cord)).unwrap() != &Tile::Empty {
             return Err(_Error::_AlreadyOccupied);
         }
-        let active = self.player_to_move();
-        self.field.insert((x_cord, y_cord), active);
-        self.turn += 1;
-        self.game_over = self.game_over(x_cord, y_cord);
-        if self.game_over {
-            return Ok(());
-        }
-        self.player_one = !self.player_one;
         Ok(())
     }
 
     pub fn place_proof(&mut self, x_cord: i32, y_cord: i32) {
-        self.field.insert((x_cord, y_cord), self.player_to_move());
+        if let Some(offer) = self.offered.take() {
+            return self.choose((x_cord, y_cord), offer);
+        }
+        // in Swap2 the player to move places stones of both players
+        let mover = match self.rules.opening {
+            Opening::Swap2 => self.owner(),
+            _ => self.player_to_move(),
+        };
+        self.put((x_cord, y_cord), mover);
+        self.turn += 1;
+        self.capture((x_cord, y_cord), mover);
+        self.game_over = self.game_over(x_cord, y_cord, mover);
+        self.player_one = self.decider() == Tile::One;
+    }
+
+    /// Chooser keeps `cell` out of the offer and Picker gets the other one.
+    fn choose(&mut self, cell: (i32, i32), (a, b): ((i32, i32), (i32, i32))) {
+        let chooser = self.player_to_move();
+        let other = if cell == a { b } else { a };
+        self.offers.push((a, b));
+        self.put(cell, chooser);
         self.turn += 1;
-        self.game_over = self.game_over(x_cord, y_cord);
-        // if !self.game_over {
-        self.player_one = !self.player_one;
-        // }
+        if other != cell {
+            self.put(other, chooser.opponent());
+            self.turn += 1;
+        }
+        // only one of the players builds, with one of the two stones
+        self.game_over = match self.rules.mode.builds(chooser) {
+            true => self.game_over(cell.0, cell.1, chooser),
+            false => self.game_over(other.0, other.1, chooser.opponent()),
+        };
+        self.player_one = true;
+    }
+
+    /// Places a stone of `tile` whoever is to move, as in random-turn games.
+    pub fn place_as(&mut self, (x_cord, y_cord): (i32, i32), tile: Tile) {
+        self.player_one = tile == Tile::One;
+        self.place_proof(x_cord, y_cord);
+    }
+
+    pub fn offer(&mut self, a: (i32, i32), b: (i32, i32)) {
+        self.offered = Some((a, b));
+        self.player_one = false;
+    }
+
+    pub fn withdraw(&mut self) {
+        self.offered = None;
+        self.player_one = true;
+    }
+
+    pub fn play(&mut self, play: Move) {
+        match play {
+            Move::Stone((x_cord, y_cord)) => self.place_proof(x_cord, y_cord),
+            Move::Offer(a, b) => self.offer(a, b),
+            decision => self.decide(decision),
+        }
+    }
+
+    pub fn unplay(&mut self, play: Move) {
+        match play {
+            Move::Stone((x_cord, y_cord)) => self.undo(x_cord, y_cord),
+            Move::Offer(..) => self.withdraw(),
+            _ => self.retract(),
+        }
+    }
+
+    /// The moves open to the player to move.
+    pub fn moves(&self) -> Vec<Move> {
+        if let Some((a, b)) = self.offered {
+            return match a == b {
+                true => vec![Move::Stone(a)],
+                false => vec![Move::Stone(a), Move::Stone(b)],
+            };
+        }
+        let empty: Vec<(i32, i32)> = self
+            .field
+            .iter()
+            .filter(|(_, tile)| **tile == Tile::Empty)
+            .map(|(cell, _)| *cell)
+            .collect();
+        if !self.rules.mode.offers() {
+            let stones = empty.into_iter().filter(|cell| self.legal(*cell));
+            return self
+                .choices()
+                .into_iter()
+                .chain(stones.map(Move::Stone))
+                .collect();
+        }
+        if let [last] = empty[..] {
+            return vec![Move::Offer(last, last)];
+        }
+        let mut offers = vec![];
+        for (i, a) in empty.iter().enumerate() {
+            for b in &empty[i + 1..] {
+                offers.push(Move::Offer(*a, *b));
+            }
+        }
+        offers
     }
 
     pub fn undo(&mut self, x_cord: i32, y_cord: i32) {
-        self.field.insert((x_cord, y_cord), Tile::Empty);
+        if self.rules.mode.offers() {
+            // every stone was the choice out of an offer
+            let (a, b) = self.offers.pop().expect("Choices follow offers.");
+            for cell in [a, b] {
+                if self.put(cell, Tile::Empty) != Some(Tile::Empty) {
+                    self.turn -= 1;
+                }
+            }
+            self.offered = Some((a, b));
+            self.game_over = false;
+            self.winner = Tile::Empty;
+            self.player_one = false;
+            return;
+        }
+        let mover = self.put((x_cord, y_cord), Tile::Empty);
+        if let Some(mover) = mover.filter(|_| self.rules.captures > 0) {
+            let taken = self.taken.pop().expect("Captures follow stones.");
+            for cell in &taken {
+                self.put(*cell, mover.opponent());
+            }
+            self.set_captures(mover, self.captures(mover) - taken.len() / 2);
+        }
         self.turn -= 1;
-        // if !self.game_over(x_cord, y_cord) {
-        self.player_one = !self.player_one;
-        // }
+        self.game_over = false;
+        self.winner = Tile::Empty;
+        self.player_one = self.decider() == Tile::One;
     }
 
-    pub fn game_over(&mut self, x_cord: i32, y_cord: i32) -> bool {
-        let active_shapes = match self.player_to_move() {
-            Tile::One => &self.shapes1,
-            Tile::Two => &self.shapes2,
-            _ => panic!("Player_to_move returned empty, which should be impossible."),
-        };
-        let mut over = false;
-        for shape in &active_shapes.shapes[..] {
-            'outer: for (s1, s2) in shape {
-                for (t1, t2) in shape {
-                    let x = s1 - t1 + x_cord;
-                    let y = s2 - t2 + y_cord;
-                    match self.field.get(&(x, y)) {
-                        None => continue 'outer,
-                        Some(tile) => match (tile, self.player_one) {
-                            (Tile::One, true) | (Tile::Two, false) => continue,
-                            (_, _) => continue 'outer,
-                        },
-                    }
-                }
-                self.winner = self.player_to_move();
-                return true;
+    /// Whether the next stone is the last one of the mover's turn.
+    pub fn ends_turn(&self) -> bool {
+        self.rules.schedule.ends_turn(self.turn)
+    }
+
+    pub fn game_over(&mut self, x_cord: i32, y_cord: i32, mover: Tile) -> bool {
+        if self.rules.goal.scores() {
+            // the shapes only count once the board is full
+            if self.draw() {
+                self.winner = self.leader();
             }
+            return self.draw();
+        }
+        let mut over = false;
+        if self.rules.mode.builds(mover) && self.forms_shape(x_cord, y_cord, mover) {
+            self.winner = self.rules.goal.winner(mover);
+            return true;
+        }
+        if self.rules.captures > 0 && self.captures(mover) >= self.rules.captures {
+            self.winner = mover;
+            return true;
         }
         if self.draw() {
             over = true;
-            self.winner = Tile::Empty;
+            // a full board is a win for Breaker, or for Maker avoiding their shape
+            self.winner = match self.rules.mode.breaker() {
+                Some(breaker) => self.rules.goal.winner(breaker),
+                None => Tile::Empty,
+            };
         }
         over
     }
 
+    /// Takes the stones that the stone of `mover` on `cell` captures and
+    /// records them for `undo`.
+    fn capture(&mut self, cell: (i32, i32), mover: Tile) {
+        if self.rules.captures == 0 {
+            return;
+        }
+        let taken = self.captured_by(cell, mover);
+        for cell in &taken {
+            self.put(*cell, Tile::Empty);
+        }
+        self.set_captures(mover, self.captures(mover) + taken.len() / 2);
+        self.taken.push(taken);
+    }
+
+    /// The stones a stone of `tile` on `cell` captures: pairs of opponent
+    /// stones in a straight line from it, flanked by a stone of `tile`
+    /// beyond them. None when the rules play without captures.
+    pub fn captured_by(&self, cell: (i32, i32), tile: Tile) -> Vec<(i32, i32)> {
+        if self.rules.captures == 0 {
+            return vec![];
+        }
+        let lattice = self.grid.lattice;
+        let steps = |cell| [lattice.neighbours(cell), lattice.diagonals(cell)].concat();
+        let mut taken = vec![];
+        for next in steps(cell) {
+            let step = (next.0 - cell.0, next.1 - cell.1);
+            let line: Vec<(i32, i32)> = (0..4)
+                .map(|i| (cell.0 + i * step.0, cell.1 + i * step.1))
+                .collect();
+            // lines run straight only where the step repeats
+            if line
+                .windows(2)
+                .any(|pair| !steps(pair[0]).contains(&pair[1]))
+            {
+                continue;
+            }
+            let located: Vec<(i32, i32)> =
+                line.iter().filter_map(|c| self.grid.locate(*c)).collect();
+            let [_, a, b, flank] = located[..] else {
+                continue;
+            };
+            let tile_at = |c| self.field.get(&c).copied();
+            if flank != cell
+                && tile_at(a) == Some(tile.opponent())
+                && tile_at(b) == Some(tile.opponent())
+                && tile_at(flank) == Some(tile)
+            {
+                taken.extend([a, b]);
+            }
+        }
+        taken
+    }
+
+    /// Whether a stone of `tile` on the empty cell captures enough pairs to win.
+    pub fn captures_win(&self, cell: (i32, i32), tile: Tile) -> bool {
+        self.rules.captures > 0
+            && self.captures(tile) + self.captured_by(cell, tile).len() / 2 >= self.rules.captures
+    }
+
+    /// The pairs `tile` has captured.
+    pub fn captures(&self, tile: Tile) -> usize {
+        match tile {
+            Tile::One => self.captures1,
+            Tile::Two => self.captures2,
+            _ => panic!("Only players capture, empty tiles do not."),
+        }
+    }
+
+    /// The stones the last placed stone captured.
+    pub fn last_captured(&self) -> &[(i32, i32)] {
+        self.taken.last().map_or(&[], |taken| taken)
+    }
+
+    /// The copies of the shapes of `tile` on the board, as many as there are
+    /// or, when they have to be disjoint, as many as share no cell.
+    pub fn score(&self, tile: Tile) -> usize {
+        let formed: Vec<&Vec<(i32, i32)>> = self
+            .placements(tile)
+            .extended()
+            .filter(|extended| self.formed(*extended, tile, None))
+            .map(|(placement, _)| placement)
+            .collect();
+        match self.rules.goal {
+            Goal::Score { disjoint: true } => packing(&formed),
+            _ => formed.len(),
+        }
+    }
+
+    /// The score of player one less the score of player two.
+    pub fn margin(&self) -> i32 {
+        self.score(Tile::One) as i32 - self.score(Tile::Two) as i32
+    }
+
+    /// The player ahead on score, or an empty tile when the scores are level.
+    pub fn leader(&self) -> Tile {
+        match self.margin().cmp(&0) {
+            std::cmp::Ordering::Greater => Tile::One,
+            std::cmp::Ordering::Less => Tile::Two,
+            std::cmp::Ordering::Equal => Tile::Empty,
+        }
+    }
+
+    /// Whether the stones of `tile` form one of its shapes through x, y, and
+    /// with it as many disjoint copies as the rules ask for.
+    pub fn forms_shape(&self, x_cord: i32, y_cord: i32, tile: Tile) -> bool {
+        let covered = |placement| self.formed(placement, tile, None);
+        self.placements(tile).through((x_cord, y_cord)).any(covered)
+            && (self.rules.copies == 1 || self.copies(tile, covered))
+    }
+
+    /// Whether playing the empty cell completes a shape of `tile`, and with
+    /// it as many disjoint copies as the rules ask for.
+    pub fn completes(&self, cell: (i32, i32), tile: Tile) -> bool {
+        let covered = |placement| self.formed(placement, tile, Some(cell));
+        self.placements(tile).through(cell).any(covered)
+            && (self.rules.copies == 1 || self.copies(tile, covered))
+    }
+
+    /// Whether the stones of `tile`, with one more on `extra`, cover the
+    /// placement and leave its extensions.
+    fn formed(
+        &self,
+        (placement, extensions): Extended,
+        tile: Tile,
+        extra: Option<(i32, i32)>,
+    ) -> bool {
+        placement
+            .iter()
+            .all(|cell| Some(*cell) == extra || self.field.get(cell) == Some(&tile))
+            && extensions
+                .iter()
+                .all(|cell| self.field.get(cell) != Some(&tile))
+    }
+
+    /// Whether the placements of `tile` that pass `filter` hold as many
+    /// pairwise disjoint ones as the rules ask for.
+    fn copies<'a>(&'a self, tile: Tile, filter: impl Fn(Extended<'a>) -> bool) -> bool {
+        let placements: Vec<&Vec<(i32, i32)>> = self
+            .placements(tile)
+            .extended()
+            .filter(|extended| filter(*extended))
+            .map(|(placement, _)| placement)
+            .collect();
+        disjoint(&placements, self.rules.copies)
+    }
+
+    /// The winner, or an empty tile for a draw, once no player can form their
+    /// shapes any more because too few placements are free of opponent stones,
+    /// with extensions free of their own. Captures can free them again, swaps
+    /// hand the stones to the other player and scores wait for a full board.
+    pub fn settled(&self) -> Option<Tile> {
+        if self.rules.captures > 0
+            || self.rules.goal.scores()
+            || (self.rules.opening.swaps() && self.turn < self.rules.opening.length())
+        {
+            return None;
+        }
+        let live = |tile: Tile| {
+            let opponent = tile.opponent();
+            self.copies(tile, |(placement, extensions)| {
+                placement
+                    .iter()
+                    .all(|cell| self.field.get(cell) != Some(&opponent))
+                    && extensions
+                        .iter()
+                        .all(|cell| self.field.get(cell) != Some(&tile))
+            })
+        };
+        match self.rules.mode.breaker() {
+            Some(breaker) => (!live(breaker.opponent())).then(|| self.rules.goal.winner(breaker)),
+            None => (!live(Tile::One) && !live(Tile::Two)).then_some(Tile::Empty),
+        }
+    }
+
+    /// Whether Breaker can keep `maker` from forming their shape by the
+    /// Erdős–Selfridge criterion: the placements free of Breaker's stones,
+    /// each weighing 2^-k for its k empty cells, weigh less than one with
+    /// Breaker to move and less than one half with Maker to move. The
+    /// criterion needs the players to alternate single stones, and Breaker to
+    /// be free to play any of them, which Black in Renju, gravity and the
+    /// opening do not allow, and stones to stay with their players, which
+    /// captures and swaps do not.
+    pub fn breaker_wins(&self, maker: Tile) -> bool {
+        let breaker = maker.opponent();
+        let empty = self.empty();
+        // weights in units of 2^-empty, which stay exact in a u128
+        if empty > 100
+            || self.rules.schedule != Schedule::default()
+            || self.rules.mode.offers()
+            || (self.rules.renju && breaker == Tile::One)
+            || self.rules.captures > 0
+            || self.rules.placement == Placement::Gravity
+            || self.turn < self.rules.opening.length()
+        {
+            return false;
+        }
+        let mut weight: u128 = 0;
+        for placement in self.placements(maker).all() {
+            let tiles = placement.iter().map(|cell| self.field.get(cell));
+            if tiles.clone().any(|tile| tile == Some(&breaker)) {
+                continue;
+            }
+            let open = tiles.filter(|tile| *tile == Some(&Tile::Empty)).count();
+            weight += 1 << (empty - open);
+        }
+        // Maker's next stone at most doubles the weight
+        if self.player_to_move() == maker {
+            weight *= 2;
+        }
+        weight < 1 << empty
+    }
+
+    pub fn placements(&self, tile: Tile) -> &Placements {
+        match tile {
+            Tile::One => &self.placements1,
+            Tile::Two => &self.placements2,
+            _ => panic!("Only players own shapes, empty tiles do not."),
+        }
+    }
+
     pub fn draw(&self) -> bool {
-        self.turn == self.size.pow(2) as usize
+        self.empty() == 0
+    }
+
+    /// The number of empty cells, captured stones leaving theirs empty again.
+    fn empty(&self) -> usize {
+        self.field.len() + 2 * (self.captures1 + self.captures2) - self.turn
+    }
+
+    /// Hash of the position that is the same for all positions symmetric to
+    /// it: the smallest Zobrist hash of its images, together with the
+    /// captured pairs and the decisions of the opening. Openings that single
+    /// out the centre leave the board its identity.
+    pub fn canonical_hash(&self) -> u64 {
+        let mut state = self.captured;
+        if !self.decisions.is_empty() {
+            state ^= calculate_hash(&self.decisions);
+        }
+        // offered cells stand out from the other empty ones
+        let offered: Vec<usize> = match self.offered {
+            Some((a, b)) if a == b => vec![self.index[&a]],
+            Some((a, b)) => vec![self.index[&a], self.index[&b]],
+            None => vec![],
+        };
+        let mut images = self
+            .symmetries
+            .iter()
+            .zip(&self.zobrist)
+            .map(|(permutation, hash)| {
+                (offered.iter()).fold(hash ^ state, |hash, i| hash ^ offer_key(permutation[*i]))
+            });
+        if self.turn < self.rules.opening.length() && !self.rules.opening.swaps() {
+            return images.next().expect("The identity is always a symmetry.");
+        }
+        images.min().expect("The identity is always a symmetry.")
     }
 
     pub fn is_over(&self) -> bool {
@@ -159,29 +1040,99 @@ impl Board {
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // edges that wrap around are drawn as ~
         let mut string = String::new();
-        for i in 0..self.size {
-            for j in 0..self.size {
-                let c = match self
-                    .field
-                    .get(&(j, i))
-                    .expect("All Coordinates should be at least Empty.")
-                {
-                    Tile::Empty => "—",
-                    Tile::One => "ⵔ",
-                    Tile::Two => "X",
+        let seam = "~ ".repeat((self.grid.width + 2 * self.grid.wraps_x() as i32) as usize);
+        if self.grid.wraps_y() {
+            string.push_str(seam.trim_end());
+            string.push('\n');
+        }
+        // layers of a cube board follow each other, split by an empty line
+        for (k, i) in (0..self.grid.depth).flat_map(|k| (0..self.grid.height).map(move |i| (k, i)))
+        {
+            if k > 0 && i == 0 {
+                string.push('\n');
+            }
+            // hex rows lean right so that neighbours touch
+            if self.grid.lattice == Lattice::Hex {
+                string.push_str(&" ".repeat(i as usize));
+            }
+            if self.grid.wraps_x() {
+                string.push_str("~ ");
+            }
+            for j in 0..self.grid.width {
+                let cell = self.grid.lattice.cell((j, i, k));
+                let c = match self.field.get(&cell) {
+                    None => "#",
+                    Some(Tile::Empty) if self.grid.lattice == Lattice::Triangle => {
+                        if (i + j) % 2 == 0 {
+                            "△"
+                        } else {
+                            "▽"
+                        }
+                    }
+                    Some(Tile::Empty) if !self.supported(cell) => "·",
+                    Some(Tile::Empty) => "—",
+                    Some(Tile::One) => "ⵔ",
+                    Some(Tile::Two) => "X",
                 };
                 string.push_str(c);
-                string.push_str(" ");
-                if j == self.size - 1 {
-                    string.push_str("\n");
+                string.push(' ');
+                if j == self.grid.width - 1 {
+                    if self.grid.wraps_x() {
+                        string.push('~');
+                    }
+                    string.push('\n');
                 }
             }
         }
+        if self.grid.wraps_y() {
+            string.push_str(seam.trim_end());
+            string.push('\n');
+        }
+        write!(f, "{}", string)
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Symmetry::C1 => "C1",
+            Symmetry::C2 => "C2",
+            Symmetry::C3 => "C3",
+            Symmetry::C4 => "C4",
+            Symmetry::C6 => "C6",
+            Symmetry::D1 => "D1",
+            Symmetry::D2 => "D2",
+            Symmetry::D3 => "D3",
+            Symmetry::D4 => "D4",
+            Symmetry::D6 => "D6",
+            Symmetry::Spatial(order) => return write!(f, "order {order}"),
+        };
         write!(f, "{}", string)
     }
 }
 
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShapeError::Empty => write!(f, "a shape needs at least one cell"),
+            ShapeError::Disconnected => write!(f, "the shape is not connected"),
+        }
+    }
+}
+
+impl Tile {
+    /// The other player, empty tiles have none.
+    pub fn opponent(&self) -> Tile {
+        match self {
+            Tile::One => Tile::Two,
+            Tile::Two => Tile::One,
+            Tile::Empty => Tile::Empty,
+        }
+    }
+}
+
 impl Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self {
@@ -193,20 +1144,421 @@ impl Display for Tile {
     }
 }
 
-pub fn get_shapes(shape: &mut Vec<(i32, i32)>) -> Vec<Vec<(i32, i32)>> {
-    let mut new_shape = vec![];
-    for _i in 0..2 {
-        for _j in 0..4 {
-            for (sx, sy) in &mut *shape {
-                let temp = *sx;
-                *sx = -(*sy);
-                *sy = temp;
+/// A Zobrist key: a fixed pseudo-random number for `feature` of the `kind`,
+/// drawn with SplitMix64 so that all boards share keys without tables.
+fn zobrist(feature: usize, kind: u64) -> u64 {
+    let mut z = ((feature as u64) << 3 | kind).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The key of `tile` on the cell at `index`, where empty cells count nothing.
+fn stone_key(index: usize, tile: Tile) -> u64 {
+    match tile {
+        Tile::Empty => 0,
+        Tile::One => zobrist(index, 1),
+        Tile::Two => zobrist(index, 2),
+    }
+}
+
+/// The key of the offered cell at `index`.
+fn offer_key(index: usize) -> u64 {
+    zobrist(index, 3)
+}
+
+/// The key of `count` pairs captured by `tile`, where none count nothing.
+fn captures_key(tile: Tile, count: usize) -> u64 {
+    match (tile, count) {
+        (_, 0) => 0,
+        (Tile::Two, _) => zobrist(count, 5),
+        _ => zobrist(count, 4),
+    }
+}
+
+fn calculate_hash<T: Hash>(t: &T) -> u64 {
+    let mut s = DefaultHasher::new();
+    t.hash(&mut s);
+    s.finish()
+}
+
+/// The cells outside the placement that continue a straight line of it: for
+/// neighbouring cells a, b of the placement, the cell beyond b seen from a.
+fn extensions(grid: &Grid, placement: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let inside = |cell| {
+        grid.locate(cell)
+            .is_some_and(|cell| placement.contains(&cell))
+    };
+    let steps = |cell| grid.lattice.neighbours(cell);
+    let mut extensions = vec![];
+    for a in placement {
+        for b in steps(*a) {
+            if !inside(b) {
+                continue;
+            }
+            let beyond = (2 * b.0 - a.0, 2 * b.1 - a.1);
+            // lines run straight only where the step repeats, which a
+            // triangle pointing up and one pointing down do not share
+            if !steps(b).contains(&beyond) {
+                continue;
+            }
+            if let Some(cell) = grid.locate(beyond) {
+                if !placement.contains(&cell) && !extensions.contains(&cell) {
+                    extensions.push(cell);
+                }
+            }
+        }
+    }
+    extensions
+}
+
+/// The symmetries of the grid that keep the rules the same. Gravity pulls
+/// stones down, so it only leaves those that keep every cell in its row.
+fn symmetries(grid: &Grid, placement: Placement) -> Vec<Vec<usize>> {
+    let cells = grid.cells();
+    let mut symmetries: Vec<Vec<usize>> = grid
+        .symmetries()
+        .into_iter()
+        .filter(|permutation| {
+            placement == Placement::Free
+                || permutation
+                    .iter()
+                    .enumerate()
+                    .all(|(i, image)| cells[i].1 == cells[*image].1)
+        })
+        .collect();
+    // the identity is the smallest permutation
+    symmetries.sort();
+    symmetries
+}
+
+/// Whether `copies` of the placements are pairwise disjoint.
+fn disjoint(placements: &[&Vec<(i32, i32)>], copies: usize) -> bool {
+    if copies == 0 {
+        return true;
+    }
+    placements.iter().enumerate().any(|(i, first)| {
+        let rest: Vec<&Vec<(i32, i32)>> = placements[i + 1..]
+            .iter()
+            .filter(|placement| placement.iter().all(|cell| !first.contains(cell)))
+            .copied()
+            .collect();
+        disjoint(&rest, copies - 1)
+    })
+}
+
+/// The most placements among them that share no cell.
+fn packing(placements: &[&Vec<(i32, i32)>]) -> usize {
+    let mut best = 0;
+    pack(placements, 0, &mut best);
+    best
+}
+
+/// Raises `best` to the most placements that share no cell, `used` of them
+/// packed already. Either a placement covers the first cell any of them
+/// has, or the cell stays empty. Branches give up once the cells left could
+/// not hold more than `best` of the smallest placements.
+fn pack(placements: &[&Vec<(i32, i32)>], used: usize, best: &mut usize) {
+    *best = (*best).max(used);
+    let cells: BTreeSet<(i32, i32)> = placements.iter().flat_map(|p| p.iter().copied()).collect();
+    let (Some(first), Some(smallest)) = (
+        cells.first(),
+        placements.iter().map(|placement| placement.len()).min(),
+    ) else {
+        return;
+    };
+    if used + cells.len() / smallest <= *best {
+        return;
+    }
+    let (covering, rest): (Vec<_>, Vec<&Vec<(i32, i32)>>) = placements
+        .iter()
+        .partition(|placement| placement.contains(first));
+    for placement in covering {
+        let apart: Vec<&Vec<(i32, i32)>> = rest
+            .iter()
+            .filter(|other| other.iter().all(|cell| !placement.contains(cell)))
+            .copied()
+            .collect();
+        pack(&apart, used + 1, best);
+    }
+    pack(&rest, used, best);
+}
+
+/// Whether the cells are edge-connected, which every polyomino has to be.
+pub fn is_connected(shape: &[(i32, i32)], lattice: Lattice) -> bool {
+    let Some(first) = shape.first() else {
+        return false;
+    };
+    let mut seen = vec![*first];
+    let mut stack = vec![*first];
+    while let Some(cell) = stack.pop() {
+        for next in lattice.neighbours(cell) {
+            if shape.contains(&next) && !seen.contains(&next) {
+                seen.push(next);
+                stack.push(next);
+            }
+        }
+    }
+    seen.len() == shape.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gomoku::grid::Topology;
+
+    fn square(cells: &[(i32, i32)]) -> Result<Shape, ShapeError> {
+        Shape::new(cells.to_vec(), Lattice::Square)
+    }
+
+    #[test]
+    fn shapes_are_normalised() {
+        let el = square(&[(3, 5), (3, 6), (3, 7), (4, 7)]).unwrap();
+        assert_eq!(el.cells(), [(0, 0), (0, 1), (0, 2), (1, 2)]);
+        assert_eq!(
+            square(&[(1, 1), (0, 0), (1, 0), (0, 0)])
+                .unwrap()
+                .cells()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn canonical_forms() {
+        let el = square(&[(0, 0), (0, 1), (0, 2), (1, 2)]).unwrap();
+        for orientation in el.orientations() {
+            let image = Shape::new(orientation, Lattice::Square).unwrap();
+            assert_eq!(image.canonical(), el.canonical());
+            assert_eq!(image.canonical().canonical(), el.canonical());
+        }
+        assert_eq!(
+            el.canonical().cells(),
+            canonical(el.cells(), Equivalence::Free, Lattice::Square)
+        );
+    }
+
+    #[test]
+    fn congruence() {
+        let el = square(&[(0, 0), (0, 1), (0, 2), (1, 2)]).unwrap();
+        // turned a quarter and mirrored
+        let turned = square(&[(0, 0), (1, 0), (2, 0), (0, 1)]).unwrap();
+        let mirrored = square(&[(1, 0), (1, 1), (1, 2), (0, 2)]).unwrap();
+        let skew = square(&[(0, 0), (1, 0), (1, 1), (2, 1)]).unwrap();
+        assert!(el.is_congruent(&turned));
+        assert!(el.is_congruent(&mirrored));
+        assert!(!el.is_congruent(&skew));
+        let skew = Shape::new(skew.cells().to_vec(), Lattice::Hex).unwrap();
+        assert!(!skew.is_congruent(&square(skew.cells()).unwrap()));
+    }
+
+    #[test]
+    fn orientations_and_symmetries() {
+        let cases = [
+            (&[(0, 0), (1, 0), (0, 1), (1, 1)][..], 1, Symmetry::D4),
+            (&[(0, 0), (1, 0), (2, 0), (3, 0)], 2, Symmetry::D2),
+            (&[(0, 0), (1, 0), (1, 1), (2, 1)], 4, Symmetry::C2),
+            (&[(0, 0), (1, 0), (2, 0), (1, 1)], 4, Symmetry::D1),
+            (&[(0, 0), (0, 1), (0, 2), (1, 2)], 8, Symmetry::C1),
+        ];
+        for (cells, orientations, symmetry) in cases {
+            let shape = square(cells).unwrap();
+            assert_eq!(shape.orientations().len(), orientations, "{cells:?}");
+            assert_eq!(shape.symmetry(), symmetry, "{cells:?}");
+        }
+        // the el and its mirror image share their orientations
+        let els = [
+            vec![(0, 0), (0, 1), (0, 2), (1, 2)],
+            vec![(1, 0), (1, 1), (1, 2), (0, 2)],
+        ];
+        assert_eq!(
+            Shapes::new(&els, Lattice::Square)
+                .unwrap()
+                .orientations()
+                .len(),
+            8
+        );
+    }
+
+    #[test]
+    fn symmetric_hex_positions_share_hashes() {
+        let grid = Grid {
+            lattice: Lattice::Hex,
+            ..Grid::rectangle(3, 3)
+        };
+        let bar = [vec![(0, 0), (1, 0), (2, 0)]];
+        let hash = |cells: &[(i32, i32)]| {
+            let mut board = Board::setup(&grid, &bar, &bar, Rules::default()).unwrap();
+            for cell in cells {
+                board.play(Move::Stone(*cell));
+            }
+            board.canonical_hash()
+        };
+        // the half turn and the reflection that swaps the axial coordinates
+        assert_eq!(hash(&[(0, 0)]), hash(&[(2, 2)]));
+        assert_eq!(hash(&[(1, 0)]), hash(&[(0, 1)]));
+        assert_eq!(hash(&[(1, 0), (2, 0)]), hash(&[(1, 2), (0, 2)]));
+        // corners with two neighbours are not those with three
+        assert_ne!(hash(&[(0, 0)]), hash(&[(2, 0)]));
+        assert_ne!(hash(&[(1, 0), (2, 0)]), hash(&[(1, 0), (0, 0)]));
+    }
+
+    #[test]
+    fn gravity_keeps_the_rows_of_symmetric_positions() {
+        let grid = Grid::rectangle(3, 3);
+        let bar = [vec![(0, 0), (1, 0), (2, 0)]];
+        let rules = Rules {
+            placement: Placement::Gravity,
+            ..Rules::default()
+        };
+        let hash = |cells: &[(i32, i32)]| {
+            let mut board = Board::setup(&grid, &bar, &bar, rules).unwrap();
+            for cell in cells {
+                board.play(Move::Stone(*cell));
             }
-            new_shape.push(shape.clone());
+            board.canonical_hash()
+        };
+        // the mirror image keeps the stones in their rows
+        assert_eq!(
+            hash(&[(0, 2), (1, 2), (1, 1)]),
+            hash(&[(2, 2), (1, 2), (1, 1)])
+        );
+        // the reflection in the other diagonal stacks the bottom row up into
+        // the first column, where the stones fell differently
+        assert_ne!(
+            hash(&[(0, 2), (1, 2), (2, 2)]),
+            hash(&[(0, 2), (0, 1), (0, 0)])
+        );
+    }
+
+    #[test]
+    fn undoing_a_win_clears_the_winner() {
+        let grid = Grid::rectangle(3, 3);
+        let domino = [vec![(0, 0), (1, 0)]];
+        let mut board = Board::setup(&grid, &domino, &domino, Rules::default()).unwrap();
+        for cell in [(0, 0), (0, 2), (1, 0)] {
+            board.place_proof(cell.0, cell.1);
         }
-        for (sx, _sy) in &mut *shape {
-            *sx = -(*sx);
+        assert_eq!(*board.winner(), Tile::One);
+        board.undo(1, 0);
+        assert!(!board.is_over());
+        assert_eq!(*board.winner(), Tile::Empty);
+        assert_eq!(board.player_to_move(), Tile::One);
+    }
+
+    #[test]
+    fn captures_keep_the_hashes_in_step() {
+        let grid = Grid::rectangle(6, 6);
+        let bar = [vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]];
+        let rules = Rules {
+            captures: 2,
+            ..Rules::default()
+        };
+        let play = |cells: &[(i32, i32)]| {
+            let mut board = Board::setup(&grid, &bar, &bar, rules).unwrap();
+            for cell in cells {
+                board.place_proof(cell.0, cell.1);
+                let zobrist = board.zobrist.clone();
+                board.rehash();
+                assert_eq!(board.zobrist, zobrist);
+            }
+            board
+        };
+        // the last stone of One captures the pair of Two either way round
+        let mut forward = play(&[(0, 0), (1, 0), (5, 5), (2, 0), (3, 0)]);
+        let backward = play(&[(3, 0), (2, 0), (5, 5), (1, 0), (0, 0)]);
+        assert_eq!(forward.last_captured(), [(2, 0), (1, 0)]);
+        assert_eq!(forward.canonical_hash(), backward.canonical_hash());
+        // the same position put together stone by stone, without a capture
+        let mut put = Board::setup(&grid, &bar, &bar, rules).unwrap();
+        for cell in [(0, 0), (3, 0), (5, 5)] {
+            put.put(cell, Tile::One);
         }
+        put.set_captures(Tile::One, 1);
+        assert_eq!(forward.canonical_hash(), put.canonical_hash());
+        // taking the capture back restores the hash before it
+        let before = play(&[(0, 0), (1, 0), (5, 5), (2, 0)]).canonical_hash();
+        assert_ne!(forward.canonical_hash(), before);
+        forward.undo(3, 0);
+        assert_eq!(forward.canonical_hash(), before);
+    }
+
+    #[test]
+    fn packings() {
+        let pack = |grid: &Grid, shape: Vec<(i32, i32)>| {
+            let shapes = Shapes::new(&[shape], grid.lattice).unwrap();
+            let placements = Placements::new(grid, &shapes, false).placements;
+            packing(&placements.iter().collect::<Vec<_>>())
+        };
+        // 8x8 takes 21 bars of three, every cell but one, out of 96 of them
+        assert_eq!(
+            pack(&Grid::rectangle(8, 8), vec![(0, 0), (1, 0), (2, 0)]),
+            21
+        );
+        assert_eq!(pack(&Grid::rectangle(7, 5), vec![(0, 0), (1, 0)]), 17);
+        // an el of four leaves out two cells of 6x5
+        let el = vec![(0, 0), (0, 1), (0, 2), (1, 2)];
+        assert_eq!(pack(&Grid::rectangle(6, 5), el), 7);
+    }
+
+    #[test]
+    fn shape_errors() {
+        assert_eq!(square(&[]), Err(ShapeError::Empty));
+        assert_eq!(square(&[(0, 0), (2, 0)]), Err(ShapeError::Disconnected));
+        assert_eq!(
+            square(&[(0, 0), (1, 1), (2, 2)]),
+            Err(ShapeError::Disconnected)
+        );
+        assert_eq!(
+            square(&[(0, 0), (1, 0), (3, 0)]),
+            Err(ShapeError::Disconnected)
+        );
+        assert_eq!(
+            Shapes::new(&[], Lattice::Square).err(),
+            Some(ShapeError::Empty)
+        );
+        let shapes = [vec![(0, 0)], vec![(0, 0), (0, 2)]];
+        assert_eq!(
+            Shapes::new(&shapes, Lattice::Square).err(),
+            Some(ShapeError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let count = |grid: &Grid, k| Placements::lines(grid, k, false).placements.len();
+        assert_eq!(count(&Grid::rectangle(3, 3), 3), 8);
+        // rows, columns and both diagonals of freestyle gomoku
+        assert_eq!(count(&Grid::rectangle(15, 15), 5), 165 + 165 + 121 + 121);
+        let cylinder = Grid {
+            topology: Topology::Cylinder,
+            ..Grid::rectangle(3, 3)
+        };
+        // rows and diagonals wrap around, and run over themselves once they
+        // are longer than the board is wide
+        assert_eq!(count(&cylinder, 3), 3 + 3 + 3 + 3);
+        assert_eq!(count(&cylinder, 4), 0);
+        let cube = |n| Grid {
+            depth: n,
+            lattice: Lattice::Cube,
+            ..Grid::rectangle(n, n)
+        };
+        // the 76 lines of Qubic, 48 along the axes, 24 across the faces and
+        // the 4 diagonals of space
+        assert_eq!(count(&cube(4), 4), 48 + 24 + 4);
+        assert_eq!(count(&cube(3), 3), 27 + 18 + 4);
+        let exact = Placements::lines(&Grid::rectangle(4, 4), 3, true);
+        let row = exact
+            .placements
+            .iter()
+            .position(|line| line == &[(0, 0), (1, 0), (2, 0)])
+            .unwrap();
+        assert_eq!(exact.extensions[row], [(3, 0)]);
+        let diagonal = exact
+            .placements
+            .iter()
+            .position(|line| line == &[(1, 1), (2, 2), (3, 3)])
+            .unwrap();
+        assert_eq!(exact.extensions[diagonal], [(0, 0)]);
     }
-    new_shape
 }
@@ -1,28 +1,33 @@
-#![allow(unused)]
 use super::game::*;
+use super::grid::Grid;
+use super::rules::{Goal, Rules};
+use super::Limits;
 use slotmap::{new_key_type, SlotMap};
 use std::cmp::min;
-use std::collections::HashSet;
-use std::{thread, time};
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time;
 
 new_key_type! {pub struct Key;}
 type Turn = (i32, i32);
 
 #[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct PNS {
     pub tree: SlotMap<Key, Node>,
     pub root: Key,
-    pub legal: HashSet<Turn>,
+    pub legal: BTreeSet<Turn>,
     pub board: Board,
     pub draw_is_loss: bool,
-    pub hashes: HashSet<u64>,
+    /// The margin of player one over player two that proves a scoring game.
+    pub threshold: i32,
+    /// Values of positions solved so far, shared between transpositions.
+    pub hashes: HashMap<u64, Status>,
+    pub limits: Limits,
 }
 
 #[derive(Debug, Clone)]
 pub struct Node {
-    pub turn: Option<Turn>,
+    pub turn: Option<Move>,
     proof: i32,
     disproof: i32,
     pub expanded: bool,
@@ -40,38 +45,46 @@ pub enum Status {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum NodeType {
     AND,
     OR,
 }
 
+impl NodeType {
+    /// Player one looks for a proof, player two for a disproof.
+    fn of(mover: Tile) -> Self {
+        match mover {
+            Tile::One => NodeType::OR,
+            _ => NodeType::AND,
+        }
+    }
+}
+
 impl PNS {
     pub fn setup(
-        size: i32,
-        shape1: &mut Vec<(i32, i32)>,
-        shape2: &mut Vec<(i32, i32)>,
+        grid: &Grid,
+        shapes1: &[Vec<(i32, i32)>],
+        shapes2: &[Vec<(i32, i32)>],
+        rules: Rules,
         draw_is_loss: bool,
         moves_made: Vec<Turn>,
-    ) -> Self {
-        let mut hs = HashSet::new();
-        for i in 0..size {
-            for j in 0..size {
-                hs.insert((i, j));
-            }
+    ) -> Result<Self, ShapeError> {
+        let mut hs = BTreeSet::new();
+        for cell in grid.cells() {
+            hs.insert(cell);
         }
         let mut sm: SlotMap<Key, Node> = SlotMap::with_key();
-        let mut board = Board::setup(size, shape1, shape2);
-        let mut root_type = match moves_made.len() % 2 {
-            0 => NodeType::OR,
-            _ => NodeType::AND,
-        };
-        let mut hashes = HashSet::new();
-        for (x_cord, y_cord) in moves_made {
-            board.place_proof(x_cord, y_cord);
-            let board_hash = calculate_hash(&board.field);
-            hs.remove(&(x_cord, y_cord));
-            hashes.insert(board_hash);
+        let mut board = Board::setup(grid, shapes1, shapes2, rules)?;
+        let hashes = HashMap::new();
+        for play in Move::record(&moves_made, rules.mode.offers()) {
+            board.play(play);
+            if let Move::Stone(cell) = play {
+                hs.remove(&cell);
+                hs.extend(board.last_captured());
+            }
         }
+        let root_type = NodeType::of(board.player_to_move());
         let root = Node {
             turn: None,
             proof: 1,
@@ -83,22 +96,28 @@ impl PNS {
             children: vec![],
         };
         let key = sm.insert(root);
-        PNS {
+        Ok(PNS {
             tree: sm,
             root: key,
             legal: hs,
             board,
             draw_is_loss,
+            threshold: 1,
             hashes,
-        }
+            limits: Limits::default(),
+        })
     }
 
     pub fn pns(&mut self, root_key: Key) -> (i32, i32) {
         self.evaluate(root_key);
         self.set_numbers(root_key);
-        let mut current = root_key.clone();
+        let mut current = root_key;
         let mut most_proving: Key;
+        let start = time::Instant::now();
         loop {
+            if self.limits.exceeded(self.tree.len(), start) {
+                break;
+            }
             let root = self.tree.get(root_key).unwrap();
             // println!(
             //     "Root proofnumber: {}, Root disproofnumber: {}",
@@ -119,12 +138,19 @@ impl PNS {
         let mut node_key = key;
         loop {
             let node = self.tree.get(node_key).unwrap();
-            let old_proof = node.proof.clone(); // maybe clone
-            let old_disproof = node.disproof.clone();
+            let old_proof = node.proof;
+            let old_disproof = node.disproof;
             self.set_numbers(node_key);
             let node = self.tree.get_mut(node_key).unwrap();
-            if node.proof == old_proof {
-                node.disproof = old_disproof;
+            if node.proof == 0 || node.disproof == 0 {
+                let state = match node.proof {
+                    0 => Status::Proven,
+                    _ => Status::Disproven,
+                };
+                self.hashes.insert(self.board.canonical_hash(), state);
+            }
+            let node = self.tree.get(node_key).unwrap();
+            if node.proof == old_proof && node.disproof == old_disproof {
                 return node_key;
             }
             if node_key == root_key {
@@ -132,20 +158,17 @@ impl PNS {
             }
             node_key = node.parent.unwrap();
             let turn = node.turn.unwrap();
-            self.board.undo(turn.0, turn.1);
-            self.legal.insert(turn);
+            self.unplay(turn);
         }
     }
 
     pub fn select_mpn(&mut self, key: Key) -> Key {
-        let ten_millis = time::Duration::from_millis(300);
-        let now = time::Instant::now();
         let mut best = key;
         let mut answer_key = key;
         loop {
             let mut value = f32::INFINITY as i32;
             let node = self.tree.get(answer_key).unwrap();
-            let n_type = node.node_type.clone();
+            let n_type = node.node_type;
             if !node.expanded {
                 break;
             }
@@ -170,8 +193,7 @@ impl PNS {
                 }
             }
             let turn = self.tree.get(best).unwrap().turn.unwrap();
-            self.board.place_proof(turn.0, turn.1);
-            self.legal.remove(&turn);
+            self.play(turn);
             answer_key = best;
         }
         answer_key
@@ -180,16 +202,22 @@ impl PNS {
     pub fn expand(&mut self, key: Key) {
         self.generate_children(key);
         let node = self.tree.get(key).unwrap();
-        let n_type = node.node_type.clone();
+        if node.children.is_empty() {
+            // Black in Renju may be left without a move, which ends in a draw
+            let draw = self.status(Tile::Empty);
+            self.tree.get_mut(key).unwrap().state = draw;
+            return;
+        }
+        let n_type = node.node_type;
         let children = node.children.clone();
         for child_key in children {
             let child = self.tree.get(child_key).unwrap();
             let turn = child.turn.expect("Function should not be called on root");
-            self.board.place_proof(turn.0, turn.1);
+            self.board.play(turn);
             self.evaluate(child_key);
             self.set_numbers(child_key);
             let child = self.tree.get(child_key).unwrap();
-            self.board.undo(turn.0, turn.1);
+            self.board.unplay(turn);
             if (n_type == NodeType::OR && child.proof == 0)
                 || (n_type == NodeType::AND && child.disproof == 0)
             {
@@ -201,24 +229,22 @@ impl PNS {
     }
 
     pub fn generate_children(&mut self, key: Key) {
-        let parent = self.tree.get(key).unwrap();
-        let node_type = match parent.node_type {
-            NodeType::AND => NodeType::OR,
-            NodeType::OR => NodeType::AND,
-        };
+        let moves = self.candidates();
         let mut child_keys = vec![];
-        for (i, j) in &self.legal {
-            self.board.place_proof(*i, *j);
-            let hash = calculate_hash(&self.board.field);
-            if self.hashes.contains(&hash) {
-                self.board.undo(*i, *j);
+        let mut siblings = HashSet::new();
+        for play in moves {
+            // children symmetric to an earlier sibling have the same value
+            self.board.play(play);
+            let fresh = siblings.insert(self.board.canonical_hash());
+            // players may place several stones before the other one moves,
+            // and an offer hands the next move to Chooser
+            let node_type = NodeType::of(self.board.player_to_move());
+            self.board.unplay(play);
+            if !fresh {
                 continue;
-            } else {
-                self.hashes.insert(hash);
             }
-            self.board.undo(*i, *j);
             let child: Node = Node {
-                turn: Some((*i, *j)),
+                turn: Some(play),
                 proof: 1,
                 disproof: 1,
                 expanded: false,
@@ -234,25 +260,149 @@ impl PNS {
         parent.children = child_keys;
     }
 
+    /// The legal moves worth searching. A player who can complete their
+    /// shape does so, and otherwise has to take the cells where the opponent
+    /// would complete theirs with their next stone, or capture a pair that
+    /// takes them out of the way. Captures that win count as completing the
+    /// shape. Avoiders only complete their shape when every move does. Offers,
+    /// choices and the decisions of the opening are all searched. Black in
+    /// Renju leaves out forbidden moves and gravity leaves out cells above
+    /// empty ones.
+    fn candidates(&self) -> Vec<Move> {
+        if self.board.rules.mode.offers() || !self.board.choices().is_empty() {
+            return self.board.moves();
+        }
+        let mover = self.board.player_to_move();
+        let all: Vec<Turn> = self
+            .legal
+            .iter()
+            .filter(|cell| self.board.legal(**cell))
+            .copied()
+            .collect();
+        let completing = |tile: Tile| -> Vec<Turn> {
+            all.iter()
+                .filter(|cell| {
+                    (self.board.rules.mode.builds(tile) && self.board.completes(**cell, tile))
+                        || self.board.captures_win(**cell, tile)
+                })
+                .copied()
+                .collect()
+        };
+        let moves = match self.board.rules.goal {
+            Goal::Achievement => {
+                let (wins, blocks) = (completing(mover), completing(mover.opponent()));
+                // with stones left in the turn the block can wait
+                let mut blocks = match self.board.ends_turn() {
+                    true => blocks,
+                    false => vec![],
+                };
+                if !blocks.is_empty() {
+                    let captures: Vec<Turn> = all
+                        .iter()
+                        .filter(|cell| !blocks.contains(cell))
+                        .filter(|cell| !self.board.captured_by(**cell, mover).is_empty())
+                        .copied()
+                        .collect();
+                    blocks.extend(captures);
+                }
+                match (wins.first(), blocks.is_empty()) {
+                    (Some(win), _) => vec![*win],
+                    (None, false) => blocks,
+                    (None, true) => all,
+                }
+            }
+            Goal::Avoidance => {
+                let losses = completing(mover);
+                match losses.len() == all.len() {
+                    true => all.into_iter().take(1).collect(),
+                    false => all.into_iter().filter(|cell| !losses.contains(cell)).collect(),
+                }
+            }
+            // a shape ends nothing while the board fills up
+            Goal::Score { .. } => all,
+        };
+        moves.into_iter().map(Move::Stone).collect()
+    }
+
+    /// Plays a move on the board, keeping track of the empty cells, which
+    /// captured stones leave empty again. Offers leave the cells empty and
+    /// the offering modes take their moves from the board.
+    fn play(&mut self, play: Move) {
+        self.board.play(play);
+        if let Move::Stone(cell) = play {
+            self.legal.remove(&cell);
+            self.legal.extend(self.board.last_captured());
+        }
+    }
+
+    fn unplay(&mut self, play: Move) {
+        if let Move::Stone(cell) = play {
+            for taken in self.board.last_captured() {
+                self.legal.remove(taken);
+            }
+            self.legal.insert(cell);
+        }
+        self.board.unplay(play);
+    }
+
     pub fn evaluate(&mut self, key: Key) {
-        let mut state;
-        if key == self.root || !self.board.is_over() {
+        let state;
+        if key == self.root {
             state = Status::Unknown;
-        } else {
-            state = match self.board.winner {
-                Tile::One => Status::Proven,
-                Tile::Two => Status::Disproven,
-                Tile::Empty => match self.draw_is_loss {
-                    true => Status::Disproven,
-                    false => Status::Proven,
+        } else if !self.board.is_over() {
+            state = match self.hashes.get(&self.board.canonical_hash()) {
+                Some(known) => *known,
+                None => match self.board.settled() {
+                    Some(winner) => self.status(winner),
+                    None => self.bounded(),
                 },
             };
+        } else if self.board.rules.goal.scores() {
+            state = match self.board.margin() >= self.threshold {
+                true => Status::Proven,
+                false => Status::Disproven,
+            };
+        } else {
+            state = self.status(self.board.winner);
         }
         self.tree.get_mut(key).unwrap().state = state;
     }
 
+    /// The status once the Erdős–Selfridge criterion shows that a player can
+    /// no longer form their shape, which settles Maker–Breaker games. In the
+    /// strong game it settles the search for the one player whose win it
+    /// is about: player one with draws as losses, player two otherwise.
+    fn bounded(&self) -> Status {
+        if self.board.rules.goal != Goal::Achievement {
+            return Status::Unknown;
+        }
+        let (maker, status) = match self.board.rules.mode.breaker() {
+            Some(breaker) => (breaker.opponent(), self.status(breaker)),
+            None => match self.draw_is_loss {
+                true => (Tile::One, Status::Disproven),
+                false => (Tile::Two, Status::Proven),
+            },
+        };
+        match self.board.breaker_wins(maker) {
+            true => status,
+            false => Status::Unknown,
+        }
+    }
+
+    /// Whether a game won by `winner` proves the root, an empty tile being a draw.
+    fn status(&self, winner: Tile) -> Status {
+        match winner {
+            Tile::One => Status::Proven,
+            Tile::Two => Status::Disproven,
+            Tile::Empty => match self.draw_is_loss {
+                true => Status::Disproven,
+                false => Status::Proven,
+            },
+        }
+    }
+
     pub fn set_numbers(&mut self, key: Key) {
-        let mut node = self.tree.get(key).unwrap();
+        let node = self.tree.get(key).unwrap();
         if node.expanded {
             let node = self.tree.get(key).unwrap();
             let mut temp_proof: i32;
@@ -281,7 +431,7 @@ impl PNS {
             node.proof = temp_proof;
             node.disproof = temp_disproof;
         } else {
-            let mut node = self.tree.get_mut(key).unwrap();
+            let node = self.tree.get_mut(key).unwrap();
             (node.proof, node.disproof) = match node.state {
                 Status::Disproven => (f32::INFINITY as i32, 0),
                 Status::Proven => (0, f32::INFINITY as i32),
@@ -290,9 +440,3 @@ impl PNS {
         }
     }
 }
-
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
-}
\ No newline at end of file
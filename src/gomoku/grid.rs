@@ -0,0 +1,624 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The cells of a board: a width x height rectangle, or a width x height x
+/// depth box on the cube lattice, without its blocked cells.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Grid {
+    pub width: i32,
+    pub height: i32,
+    /// The number of layers, one for every lattice but the cube lattice.
+    pub depth: i32,
+    pub blocked: BTreeSet<(i32, i32)>,
+    pub topology: Topology,
+    pub lattice: Lattice,
+}
+
+/// How cells tile the plane. Hex cells use axial coordinates, so a width x
+/// height board is a rhombus and x, y neighbours x + 1, y - 1 and x - 1, y + 1.
+/// Triangle cells alternate along each row, x, y points up when x + y is
+/// even and down otherwise, and only shares an edge with the row above or
+/// below it respectively. Cube cells fill space and are stored as
+/// x + z * `LAYER`, y so that boards and shapes keep two coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lattice {
+    Square,
+    Hex,
+    Triangle,
+    Cube,
+}
+
+/// How far apart the layers of cube cells are stored along x. Coordinates
+/// of a cube cell have to stay below half of it.
+pub const LAYER: i32 = 1 << 12;
+
+/// A linear map of x, y, z given by the rows of its matrix.
+pub type Transform = [[i32; 3]; 3];
+
+/// Extends the planar map x, y -> a x + b y, c x + d y to space.
+const fn planar([a, b, c, d]: [i32; 4]) -> Transform {
+    [[a, b, 0], [c, d, 0], [0, 0, 1]]
+}
+
+const SQUARE_TRANSFORMS: [Transform; 8] = [
+    planar([1, 0, 0, 1]),
+    planar([0, -1, 1, 0]),
+    planar([-1, 0, 0, -1]),
+    planar([0, 1, -1, 0]),
+    planar([-1, 0, 0, 1]),
+    planar([0, 1, 1, 0]),
+    planar([1, 0, 0, -1]),
+    planar([0, -1, -1, 0]),
+];
+
+/// Rotations by multiples of 60 degrees, then the same rotations followed
+/// by swapping the axial coordinates.
+const HEX_TRANSFORMS: [Transform; 12] = [
+    planar([1, 0, 0, 1]),
+    planar([0, -1, 1, 1]),
+    planar([-1, -1, 1, 0]),
+    planar([-1, 0, 0, -1]),
+    planar([0, 1, -1, -1]),
+    planar([1, 1, -1, 0]),
+    planar([0, 1, 1, 0]),
+    planar([1, 1, 0, -1]),
+    planar([1, 0, -1, -1]),
+    planar([0, -1, -1, 0]),
+    planar([-1, -1, 0, 1]),
+    planar([-1, 0, 1, 1]),
+];
+
+const CUBE_TRANSFORMS: [Transform; 48] = cube_transforms();
+
+/// The 48 signed permutation matrices, the 24 rotations of the cube first.
+const fn cube_transforms() -> [Transform; 48] {
+    // permutations of the axes with their parity
+    let permutations = [
+        ([0, 1, 2], 1),
+        ([1, 2, 0], 1),
+        ([2, 0, 1], 1),
+        ([0, 2, 1], -1),
+        ([2, 1, 0], -1),
+        ([1, 0, 2], -1),
+    ];
+    let mut transforms = [[[0; 3]; 3]; 48];
+    let (mut rotations, mut reflections) = (0, 24);
+    let mut p = 0;
+    while p < 6 {
+        let (axes, parity) = permutations[p];
+        let mut signs = 0;
+        while signs < 8 {
+            let mut t = [[0; 3]; 3];
+            let mut determinant = parity;
+            let mut row = 0;
+            while row < 3 {
+                let sign = if signs & (1 << row) == 0 { 1 } else { -1 };
+                t[row][axes[row]] = sign;
+                determinant *= sign;
+                row += 1;
+            }
+            if determinant == 1 {
+                transforms[rotations] = t;
+                rotations += 1;
+            } else {
+                transforms[reflections] = t;
+                reflections += 1;
+            }
+            signs += 1;
+        }
+        p += 1;
+    }
+    transforms
+}
+
+impl Lattice {
+    /// The symmetries of the lattice that fix the origin, rotations first.
+    pub fn transforms(&self) -> &'static [Transform] {
+        match self {
+            Lattice::Square => &SQUARE_TRANSFORMS,
+            // triangles turn with the vertices of the lattice, which is a hex lattice
+            Lattice::Hex | Lattice::Triangle => &HEX_TRANSFORMS,
+            Lattice::Cube => &CUBE_TRANSFORMS,
+        }
+    }
+
+    /// The image of a cell under one of `transforms`.
+    pub fn transform(&self, t: Transform, (x, y): (i32, i32)) -> (i32, i32) {
+        match self {
+            Lattice::Square | Lattice::Hex => transform(t, (x, y)),
+            Lattice::Cube => {
+                let (x, y, z) = self.coordinates((x, y));
+                let row = |r: [i32; 3]| r[0] * x + r[1] * y + r[2] * z;
+                self.cell((row(t[0]), row(t[1]), row(t[2])))
+            }
+            Lattice::Triangle => {
+                // Three times the centre of the triangle in axial vertex
+                // coordinates is an integer point that the transforms act on
+                // linearly. Up triangles have their apex at vertex
+                // (x - y) / 2, y, down triangles the left end of their top
+                // edge at (x - y - 1) / 2, y.
+                let centre = match (x + y).rem_euclid(2) {
+                    0 => (3 * (x - y).div_euclid(2) - 1, 3 * y + 2),
+                    _ => (3 * (x - y - 1).div_euclid(2) + 1, 3 * y + 1),
+                };
+                let (p, q) = transform(t, centre);
+                match q.rem_euclid(3) {
+                    2 => {
+                        let y = (q - 2).div_euclid(3);
+                        (2 * (p + 1).div_euclid(3) + y, y)
+                    }
+                    _ => {
+                        let y = (q - 1).div_euclid(3);
+                        (2 * (p - 1).div_euclid(3) + y + 1, y)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether moving every cell by dx, dy maps the lattice onto itself,
+    /// which on the triangle lattice has to keep up triangles pointing up.
+    pub fn is_translation(&self, (dx, dy): (i32, i32)) -> bool {
+        *self != Lattice::Triangle || (dx + dy) % 2 == 0
+    }
+
+    /// How many of `transforms` are rotations.
+    pub fn rotations(&self) -> usize {
+        self.transforms().len() / 2
+    }
+
+    /// The cells touching x, y only at a corner that continue straight
+    /// lines, along which Pente also captures. Only the square lattice has them.
+    pub fn diagonals(&self, (x, y): (i32, i32)) -> Vec<(i32, i32)> {
+        match self {
+            Lattice::Square => vec![
+                (x + 1, y + 1),
+                (x - 1, y - 1),
+                (x + 1, y - 1),
+                (x - 1, y + 1),
+            ],
+            _ => vec![],
+        }
+    }
+
+    /// The steps in x, y, z along which straight lines run, one of each
+    /// pair of opposite steps: the axes and, on the square and cube
+    /// lattices, the diagonals, on cubes those of the faces and of space.
+    /// Triangles do not line up along any of them.
+    pub fn directions(&self) -> Vec<(i32, i32, i32)> {
+        match self {
+            Lattice::Square => vec![(1, 0, 0), (0, 1, 0), (1, 1, 0), (1, -1, 0)],
+            Lattice::Hex => vec![(1, 0, 0), (0, 1, 0), (1, -1, 0)],
+            Lattice::Triangle => vec![],
+            Lattice::Cube => {
+                let steps = [-1, 0, 1];
+                let mut directions = vec![];
+                for dx in steps {
+                    for dy in steps {
+                        for dz in steps {
+                            // the first step that moves at all goes forward
+                            if [dx, dy, dz].into_iter().find(|d| *d != 0) == Some(1) {
+                                directions.push((dx, dy, dz));
+                            }
+                        }
+                    }
+                }
+                directions
+            }
+        }
+    }
+
+    pub fn neighbours(&self, (x, y): (i32, i32)) -> Vec<(i32, i32)> {
+        match self {
+            Lattice::Square => vec![(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)],
+            Lattice::Hex => vec![
+                (x + 1, y),
+                (x - 1, y),
+                (x, y + 1),
+                (x, y - 1),
+                (x + 1, y - 1),
+                (x - 1, y + 1),
+            ],
+            Lattice::Triangle if (x + y) % 2 == 0 => vec![(x + 1, y), (x - 1, y), (x, y + 1)],
+            Lattice::Triangle => vec![(x + 1, y), (x - 1, y), (x, y - 1)],
+            Lattice::Cube => vec![
+                (x + 1, y),
+                (x - 1, y),
+                (x, y + 1),
+                (x, y - 1),
+                (x + LAYER, y),
+                (x - LAYER, y),
+            ],
+        }
+    }
+
+    /// The x, y, z coordinates of a cell, z is zero off the cube lattice.
+    pub fn coordinates(&self, (x, y): (i32, i32)) -> (i32, i32, i32) {
+        match self {
+            Lattice::Cube => {
+                let z = (x + LAYER / 2).div_euclid(LAYER);
+                (x - z * LAYER, y, z)
+            }
+            _ => (x, y, 0),
+        }
+    }
+
+    /// The cell at x, y, z, the inverse of `coordinates`.
+    pub fn cell(&self, (x, y, z): (i32, i32, i32)) -> (i32, i32) {
+        match self {
+            Lattice::Cube => (x + z * LAYER, y),
+            _ => (x, y),
+        }
+    }
+}
+
+pub fn transform(t: Transform, (x, y): (i32, i32)) -> (i32, i32) {
+    (t[0][0] * x + t[0][1] * y, t[1][0] * x + t[1][1] * y)
+}
+
+/// Which edges of the rectangle are glued together, so shapes can wrap around them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topology {
+    Plane,
+    /// The left and right edges meet.
+    Cylinder,
+    /// The left and right as well as the top and bottom edges meet.
+    Torus,
+}
+
+impl Grid {
+    pub fn rectangle(width: i32, height: i32) -> Self {
+        Grid {
+            width,
+            height,
+            depth: 1,
+            blocked: BTreeSet::new(),
+            topology: Topology::Plane,
+            lattice: Lattice::Square,
+        }
+    }
+
+    /// The grid with the given cells blocked, cells outside it are ignored.
+    pub fn with_blocked(mut self, blocked: impl IntoIterator<Item = (i32, i32)>) -> Self {
+        self.blocked = blocked
+            .into_iter()
+            .filter(|cell| self.in_bounds(*cell))
+            .collect();
+        self
+    }
+
+    /// The playable cells, ordered by layer, x and then y.
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        let mut cells = vec![];
+        for k in 0..self.depth {
+            for i in 0..self.width {
+                for j in 0..self.height {
+                    let cell = self.lattice.cell((i, j, k));
+                    if !self.blocked.contains(&cell) {
+                        cells.push(cell);
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    pub fn contains(&self, cell: (i32, i32)) -> bool {
+        self.in_bounds(cell) && !self.blocked.contains(&cell)
+    }
+
+    pub fn in_bounds(&self, cell: (i32, i32)) -> bool {
+        let (x, y, z) = self.lattice.coordinates(cell);
+        0 <= x && x < self.width && 0 <= y && y < self.height && 0 <= z && z < self.depth
+    }
+
+    pub fn wraps_x(&self) -> bool {
+        self.topology != Topology::Plane
+    }
+
+    pub fn wraps_y(&self) -> bool {
+        self.topology == Topology::Torus
+    }
+
+    /// Whether glued edges join cells that fit together, which triangles only
+    /// do across an even number of cells and cubes not at all.
+    pub fn seams_match(&self) -> bool {
+        let even = |length: i32| length % 2 == 0;
+        match self.lattice {
+            Lattice::Square | Lattice::Hex => true,
+            Lattice::Triangle => {
+                (!self.wraps_x() || even(self.width)) && (!self.wraps_y() || even(self.height))
+            }
+            Lattice::Cube => self.topology == Topology::Plane,
+        }
+    }
+
+    /// The cell in the middle of the board, right of and below it when the
+    /// board has no single middle cell.
+    pub fn centre(&self) -> (i32, i32) {
+        self.lattice
+            .cell((self.width / 2, self.height / 2, self.depth / 2))
+    }
+
+    /// The playable cell that x, y lands on once wrapped around the glued edges.
+    pub fn locate(&self, (x, y): (i32, i32)) -> Option<(i32, i32)> {
+        let x = if self.wraps_x() {
+            x.rem_euclid(self.width)
+        } else {
+            x
+        };
+        let y = if self.wraps_y() {
+            y.rem_euclid(self.height)
+        } else {
+            y
+        };
+        self.contains((x, y)).then_some((x, y))
+    }
+
+    /// Every way to put one of the orientations on the board, as sorted cells.
+    /// Placements that wrap onto themselves are left out.
+    pub fn placements(&self, orientations: &[Vec<(i32, i32)>]) -> Vec<Vec<(i32, i32)>> {
+        let mut placements = BTreeSet::new();
+        let xs = if self.wraps_x() { 0 } else { -self.width };
+        let ys = if self.wraps_y() { 0 } else { -self.height };
+        let zs = if self.depth > 1 { -self.depth } else { 0 };
+        for orientation in orientations {
+            for dz in zs..self.depth {
+                for dx in xs..self.width {
+                    for dy in ys..self.height {
+                        if !self.lattice.is_translation((dx, dy)) {
+                            continue;
+                        }
+                        let (ox, oy) = self.lattice.cell((dx, dy, dz));
+                        let cells: Option<BTreeSet<(i32, i32)>> = orientation
+                            .iter()
+                            .map(|(x, y)| self.locate((x + ox, y + oy)))
+                            .collect();
+                        match cells {
+                            Some(cells) if cells.len() == orientation.len() => {
+                                placements.insert(cells.into_iter().collect());
+                            }
+                            _ => continue,
+                        }
+                    }
+                }
+            }
+        }
+        placements.into_iter().collect()
+    }
+
+    /// The symmetries of the lattice, combined with the translations along
+    /// wrapping axes, that map the playable cells onto themselves and glued
+    /// edges onto glued edges. Each is a permutation of the indices of `cells`.
+    pub fn symmetries(&self) -> Vec<Vec<usize>> {
+        let mut periods = vec![];
+        if self.wraps_x() {
+            periods.push((self.width, 0));
+        }
+        if self.wraps_y() {
+            periods.push((0, self.height));
+        }
+        let is_period = |(x, y): (i32, i32)| {
+            let x_ok = if self.wraps_x() {
+                x % self.width == 0
+            } else {
+                x == 0
+            };
+            let y_ok = if self.wraps_y() {
+                y % self.height == 0
+            } else {
+                y == 0
+            };
+            x_ok && y_ok
+        };
+        let lowest = |cells: &[(i32, i32)]| {
+            let coordinates: Vec<(i32, i32, i32)> = cells
+                .iter()
+                .map(|cell| self.lattice.coordinates(*cell))
+                .collect();
+            (
+                coordinates.iter().map(|c| c.0).min().unwrap_or(0),
+                coordinates.iter().map(|c| c.1).min().unwrap_or(0),
+                coordinates.iter().map(|c| c.2).min().unwrap_or(0),
+            )
+        };
+        let cells = self.cells();
+        let (min_x, min_y, min_z) = lowest(&cells);
+        let mut symmetries = vec![];
+        for t in self.lattice.transforms() {
+            let origin = self.lattice.transform(*t, (0, 0));
+            let moved = |(x, y): (i32, i32)| {
+                let (image_x, image_y) = self.lattice.transform(*t, (x, y));
+                (image_x - origin.0, image_y - origin.1)
+            };
+            if !periods.iter().all(|period| is_period(moved(*period))) {
+                continue;
+            }
+            let images: Vec<(i32, i32)> = cells
+                .iter()
+                .map(|cell| self.lattice.transform(*t, *cell))
+                .collect();
+            // off the glued axes the images have to line up with the cells
+            let (image_x, image_y, image_z) = lowest(&images);
+            let (dx, dy, dz) = (min_x - image_x, min_y - image_y, min_z - image_z);
+            let xs = if self.wraps_x() {
+                0..self.width
+            } else {
+                dx..dx + 1
+            };
+            let ys = if self.wraps_y() {
+                0..self.height
+            } else {
+                dy..dy + 1
+            };
+            for dx in xs {
+                for dy in ys.clone() {
+                    if !self.lattice.is_translation((dx, dy)) {
+                        continue;
+                    }
+                    let (ox, oy) = self.lattice.cell((dx, dy, dz));
+                    let permutation: Option<Vec<usize>> = images
+                        .iter()
+                        .map(|(x, y)| {
+                            let image = self.locate((x + ox, y + oy))?;
+                            cells.binary_search(&image).ok()
+                        })
+                        .collect();
+                    symmetries.extend(permutation);
+                }
+            }
+        }
+        symmetries
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plane" => Ok(Topology::Plane),
+            "cylinder" => Ok(Topology::Cylinder),
+            "torus" => Ok(Topology::Torus),
+            _ => Err("expected one of plane, cylinder, torus".to_string()),
+        }
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Topology::Plane => "plane",
+            Topology::Cylinder => "cylinder",
+            Topology::Torus => "torus",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl FromStr for Lattice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "square" => Ok(Lattice::Square),
+            "hex" => Ok(Lattice::Hex),
+            "triangle" => Ok(Lattice::Triangle),
+            "cube" => Ok(Lattice::Cube),
+            _ => Err("expected one of square, hex, triangle, cube".to_string()),
+        }
+    }
+}
+
+impl Display for Lattice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Lattice::Square => "square",
+            Lattice::Hex => "hex",
+            Lattice::Triangle => "triangle",
+            Lattice::Cube => "cube",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(width: i32, height: i32, lattice: Lattice, topology: Topology) -> Grid {
+        Grid {
+            lattice,
+            topology,
+            ..Grid::rectangle(width, height)
+        }
+    }
+
+    fn square(width: i32, height: i32) -> Grid {
+        grid(width, height, Lattice::Square, Topology::Plane)
+    }
+
+    /// Whether every symmetry maps the cells onto themselves, once each.
+    fn permutes(grid: &Grid) -> bool {
+        let cells = grid.cells().len();
+        grid.symmetries().iter().all(|permutation| {
+            let mut sorted = permutation.clone();
+            sorted.sort();
+            sorted == (0..cells).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn rectangles() {
+        assert_eq!(square(4, 4).symmetries().len(), 8);
+        assert_eq!(square(4, 3).symmetries().len(), 4);
+        assert_eq!(square(5, 1).symmetries().len(), 4);
+        assert_eq!(square(1, 1).symmetries().len(), 8);
+        assert!(permutes(&square(4, 3)));
+    }
+
+    #[test]
+    fn masks() {
+        assert_eq!(square(3, 3).with_blocked([(1, 1)]).symmetries().len(), 8);
+        // the diagonal through the blocked corner
+        assert_eq!(square(3, 3).with_blocked([(0, 0)]).symmetries().len(), 2);
+        assert_eq!(square(4, 3).with_blocked([(0, 0)]).symmetries().len(), 1);
+        assert!(permutes(&square(3, 3).with_blocked([(0, 0)])));
+    }
+
+    #[test]
+    fn blocked_first_column() {
+        // what is left is a 3 x 3 square, however far it is from the origin
+        let column = square(4, 3).with_blocked([(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(column.symmetries().len(), 8);
+        let row = square(3, 4).with_blocked([(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(row.symmetries().len(), 8);
+        assert!(permutes(&column) && permutes(&row));
+    }
+
+    #[test]
+    fn wrapping() {
+        let cylinder = |width, height| grid(width, height, Lattice::Square, Topology::Cylinder);
+        let torus = |width, height| grid(width, height, Lattice::Square, Topology::Torus);
+        // every turn along the seam, with both mirror images and the half turn
+        assert_eq!(cylinder(4, 3).symmetries().len(), 4 * 4);
+        assert_eq!(cylinder(4, 4).symmetries().len(), 4 * 4);
+        // every translation with the symmetries of the rectangle
+        assert_eq!(torus(4, 3).symmetries().len(), 4 * 12);
+        assert_eq!(torus(4, 4).symmetries().len(), 8 * 16);
+        assert!(permutes(&torus(4, 3)) && permutes(&cylinder(4, 3)));
+    }
+
+    #[test]
+    fn hex_rhombi() {
+        let hex = |width, height| grid(width, height, Lattice::Hex, Topology::Plane);
+        // the half turn and, on a rhombus, the reflections in both diagonals
+        assert_eq!(hex(3, 3).symmetries().len(), 4);
+        assert_eq!(hex(4, 3).symmetries().len(), 2);
+        assert!(permutes(&hex(3, 3)));
+    }
+
+    #[test]
+    fn triangles() {
+        let triangle = |width, height| grid(width, height, Lattice::Triangle, Topology::Plane);
+        // five triangles in two rows make a hexagon with edges of two, three
+        // and two, with both mirror images and the half turn, four of them
+        // one that leans, symmetric only top to bottom
+        assert_eq!(triangle(5, 2).symmetries().len(), 4);
+        assert_eq!(triangle(4, 2).symmetries().len(), 2);
+        assert!(permutes(&triangle(5, 2)));
+    }
+
+    #[test]
+    fn cubes() {
+        let cube = |width, height, depth| Grid {
+            depth,
+            ..grid(width, height, Lattice::Cube, Topology::Plane)
+        };
+        assert_eq!(cube(3, 3, 3).symmetries().len(), 48);
+        assert_eq!(cube(3, 3, 2).symmetries().len(), 16);
+        assert_eq!(cube(4, 3, 2).symmetries().len(), 8);
+        assert!(permutes(&cube(3, 3, 2)));
+    }
+}
@@ -0,0 +1,204 @@
+use super::grid::Lattice;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Which transformations turn two polyominoes into the same one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Equivalence {
+    /// Rotations and reflections.
+    Free,
+    /// Rotations only.
+    OneSided,
+    /// Translations only.
+    Fixed,
+}
+
+/// Every polyomino, or polyhex, polyiamond or polycube on the other
+/// lattices, with `size` cells up to `equivalence`, each in the canonical
+/// form returned by `canonical`, in increasing order.
+pub fn enumerate(size: usize, equivalence: Equivalence, lattice: Lattice) -> Vec<Vec<(i32, i32)>> {
+    if size == 0 {
+        return vec![];
+    }
+    // start from an up and a down triangle, which are one cell elsewhere
+    let mut current: BTreeSet<Vec<(i32, i32)>> = [(0, 0), (0, 1)]
+        .into_iter()
+        .map(|cell| canonical(&[cell], equivalence, lattice))
+        .collect();
+    for _ in 1..size {
+        let mut next = BTreeSet::new();
+        for shape in &current {
+            for cell in shape {
+                for cell in lattice.neighbours(*cell) {
+                    if shape.contains(&cell) {
+                        continue;
+                    }
+                    let mut grown = shape.clone();
+                    grown.push(cell);
+                    next.insert(canonical(&grown, equivalence, lattice));
+                }
+            }
+        }
+        current = next;
+    }
+    current.into_iter().collect()
+}
+
+/// The smallest normalised orientation of the shape that `equivalence` allows.
+pub fn canonical(
+    shape: &[(i32, i32)],
+    equivalence: Equivalence,
+    lattice: Lattice,
+) -> Vec<(i32, i32)> {
+    let transforms = match equivalence {
+        Equivalence::Fixed => return normalise(shape.to_vec(), lattice),
+        Equivalence::OneSided => &lattice.transforms()[..lattice.rotations()],
+        Equivalence::Free => lattice.transforms(),
+    };
+    transforms
+        .iter()
+        .map(|t| {
+            let image = shape
+                .iter()
+                .map(|cell| lattice.transform(*t, *cell))
+                .collect();
+            normalise(image, lattice)
+        })
+        .min()
+        .expect("Every lattice has the identity.")
+}
+
+/// Translates the cells so that their smallest coordinates are zero and sorts
+/// them. Triangles keep pointing the same way, so a shape whose leftmost cell
+/// would change direction starts at x = 1 instead. Cubes are moved along all
+/// three axes.
+pub fn normalise(mut shape: Vec<(i32, i32)>, lattice: Lattice) -> Vec<(i32, i32)> {
+    if lattice == Lattice::Cube {
+        let coordinates: Vec<(i32, i32, i32)> = shape
+            .iter()
+            .map(|cell| lattice.coordinates(*cell))
+            .collect();
+        let min_x = coordinates.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_y = coordinates.iter().map(|c| c.1).min().unwrap_or(0);
+        let min_z = coordinates.iter().map(|c| c.2).min().unwrap_or(0);
+        let mut shape: Vec<(i32, i32)> = coordinates
+            .into_iter()
+            .map(|(x, y, z)| lattice.cell((x - min_x, y - min_y, z - min_z)))
+            .collect();
+        shape.sort();
+        return shape;
+    }
+    let mut min_x = shape.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let min_y = shape.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    if !lattice.is_translation((min_x, min_y)) {
+        min_x -= 1;
+    }
+    for (x, y) in &mut shape {
+        *x -= min_x;
+        *y -= min_y;
+    }
+    shape.sort();
+    shape
+}
+
+impl FromStr for Equivalence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(Equivalence::Free),
+            "one-sided" => Ok(Equivalence::OneSided),
+            "fixed" => Ok(Equivalence::Fixed),
+            _ => Err("expected one of free, one-sided, fixed".to_string()),
+        }
+    }
+}
+
+impl Display for Equivalence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Equivalence::Free => "free",
+            Equivalence::OneSided => "one-sided",
+            Equivalence::Fixed => "fixed",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(equivalence: Equivalence, lattice: Lattice, largest: usize) -> Vec<usize> {
+        (1..=largest)
+            .map(|size| enumerate(size, equivalence, lattice).len())
+            .collect()
+    }
+
+    #[test]
+    fn polyominoes() {
+        // OEIS A000105, A000988 and A001168
+        let square = Lattice::Square;
+        assert_eq!(
+            counts(Equivalence::Free, square, 7),
+            [1, 1, 2, 5, 12, 35, 108]
+        );
+        assert_eq!(
+            counts(Equivalence::OneSided, square, 7),
+            [1, 1, 2, 7, 18, 60, 196]
+        );
+        assert_eq!(
+            counts(Equivalence::Fixed, square, 7),
+            [1, 2, 6, 19, 63, 216, 760]
+        );
+    }
+
+    #[test]
+    fn polyhexes() {
+        // OEIS A000228, A006535 and A001207
+        let hex = Lattice::Hex;
+        assert_eq!(counts(Equivalence::Free, hex, 6), [1, 1, 3, 7, 22, 82]);
+        assert_eq!(
+            counts(Equivalence::OneSided, hex, 6),
+            [1, 1, 3, 10, 33, 147]
+        );
+        assert_eq!(counts(Equivalence::Fixed, hex, 6), [1, 3, 11, 44, 186, 814]);
+    }
+
+    #[test]
+    fn polyiamonds() {
+        // OEIS A000577, A006534 and A001420
+        let triangle = Lattice::Triangle;
+        assert_eq!(
+            counts(Equivalence::Free, triangle, 7),
+            [1, 1, 1, 3, 4, 12, 24]
+        );
+        assert_eq!(
+            counts(Equivalence::OneSided, triangle, 7),
+            [1, 1, 1, 4, 6, 19, 43]
+        );
+        assert_eq!(
+            counts(Equivalence::Fixed, triangle, 7),
+            [2, 3, 6, 14, 36, 94, 250]
+        );
+    }
+
+    #[test]
+    fn polycubes() {
+        // OEIS A038119, A000162 and A001931
+        let cube = Lattice::Cube;
+        assert_eq!(counts(Equivalence::Free, cube, 5), [1, 1, 2, 7, 23]);
+        assert_eq!(counts(Equivalence::OneSided, cube, 5), [1, 1, 2, 8, 29]);
+        assert_eq!(counts(Equivalence::Fixed, cube, 5), [1, 3, 15, 86, 534]);
+    }
+
+    #[test]
+    fn enumerated_shapes_are_canonical() {
+        for equivalence in [Equivalence::Free, Equivalence::OneSided, Equivalence::Fixed] {
+            for shape in enumerate(5, equivalence, Lattice::Square) {
+                assert_eq!(canonical(&shape, equivalence, Lattice::Square), shape);
+            }
+        }
+    }
+}
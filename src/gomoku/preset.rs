@@ -0,0 +1,175 @@
+use super::rules::Exact;
+use std::str::FromStr;
+
+/// A game of k in a row on an m by n board, where rows, columns and both
+/// diagonals count: tic-tac-toe, the other m,n,k-games, gomoku and Pente,
+/// or on a cube of m by n by m cells as in Qubic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    pub width: i32,
+    pub height: i32,
+    pub k: usize,
+    /// Whether only lines of exactly k count, as in standard gomoku.
+    pub exact: Exact,
+    /// Whether Black may not play overlines, double-fours and double-threes.
+    pub renju: bool,
+    /// The captured pairs that win, none when zero.
+    pub captures: usize,
+    /// Whether the board is a cube, with the lines through its layers and
+    /// the diagonals of its faces and of space counting too.
+    pub cube: bool,
+}
+
+/// The presets known by name, each with its aliases.
+const NAMED: [(&str, &[&str], Preset); 6] = [
+    (
+        "tic-tac-toe",
+        &["noughts-and-crosses"],
+        Preset {
+            width: 3,
+            height: 3,
+            k: 3,
+            exact: Exact::Neither,
+            renju: false,
+            captures: 0,
+            cube: false,
+        },
+    ),
+    (
+        "freestyle-gomoku",
+        &["freestyle", "gomoku"],
+        Preset {
+            width: 15,
+            height: 15,
+            k: 5,
+            exact: Exact::Neither,
+            renju: false,
+            captures: 0,
+            cube: false,
+        },
+    ),
+    (
+        "standard-gomoku",
+        &["standard"],
+        Preset {
+            width: 15,
+            height: 15,
+            k: 5,
+            exact: Exact::Both,
+            renju: false,
+            captures: 0,
+            cube: false,
+        },
+    ),
+    (
+        "renju",
+        &[],
+        Preset {
+            width: 15,
+            height: 15,
+            k: 5,
+            exact: Exact::One,
+            renju: true,
+            captures: 0,
+            cube: false,
+        },
+    ),
+    (
+        "pente",
+        &[],
+        Preset {
+            width: 19,
+            height: 19,
+            k: 5,
+            exact: Exact::Neither,
+            renju: false,
+            captures: 5,
+            cube: false,
+        },
+    ),
+    (
+        "qubic",
+        &[],
+        Preset {
+            width: 4,
+            height: 4,
+            k: 4,
+            exact: Exact::Neither,
+            renju: false,
+            captures: 0,
+            cube: true,
+        },
+    ),
+];
+
+impl Preset {
+    /// A row of k cells, which the rules lay along every line of the board.
+    pub fn shapes(&self) -> Vec<Vec<(i32, i32)>> {
+        vec![(0..).take(self.k).map(|i| (i, 0)).collect()]
+    }
+
+    /// Names the shapes, like 5-in-a-row.
+    pub fn label(&self) -> String {
+        format!("{}-in-a-row", self.k)
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    /// Reads a preset by name or as `m,n,k`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, _, preset)) = NAMED
+            .iter()
+            .find(|(name, aliases, _)| *name == s || aliases.contains(&s))
+        {
+            return Ok(*preset);
+        }
+        let invalid = || {
+            format!("expected m,n,k like 4,4,4, tic-tac-toe, freestyle-gomoku, standard-gomoku, renju, pente or qubic, found '{s}'")
+        };
+        let numbers: Vec<usize> = s
+            .split(',')
+            .map(|number| number.trim().parse::<usize>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid())?;
+        match numbers[..] {
+            [m, n, k] if m > 0 && n > 0 && k > 0 && k <= m.max(n) => {
+                let (Ok(width), Ok(height)) = (i32::try_from(m), i32::try_from(n)) else {
+                    return Err(format!(
+                        "m,n,k too large, expected m and n up to {}, found '{s}'",
+                        i32::MAX
+                    ));
+                };
+                Ok(Preset {
+                    width,
+                    height,
+                    k,
+                    exact: Exact::Neither,
+                    renju: false,
+                    captures: 0,
+                    cube: false,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn m_n_k() {
+        let game: Preset = "7,6,4".parse().unwrap();
+        assert_eq!((game.width, game.height, game.k), (7, 6, 4));
+        assert_eq!(game.shapes(), [[(0, 0), (1, 0), (2, 0), (3, 0)]]);
+        assert_eq!("tic-tac-toe".parse(), "3,3,3".parse::<Preset>());
+        for invalid in ["0,3,3", "3,3,0", "3,3,4", "3,3", "3,3,3,3", "a,3,3"] {
+            assert!(invalid.parse::<Preset>().is_err(), "{invalid}");
+        }
+        let large = "3000000000,3,3".parse::<Preset>().unwrap_err();
+        assert!(large.starts_with("m,n,k too large"), "{large}");
+    }
+}
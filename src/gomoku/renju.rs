@@ -0,0 +1,129 @@
+use super::game::{Board, Tile};
+
+/// Rows, columns and both diagonals, each in one of its two senses.
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// What a cell means for Black's lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Point {
+    Black,
+    Empty,
+    /// A white stone or a cell off the board, which ends a line.
+    Blocked,
+}
+
+/// Whether Black may not play the empty cell: a five of exactly k stones is
+/// always allowed, otherwise Black may not make an overline, two fours or
+/// two open threes at once. A four can be completed to a five by one more
+/// stone, an open three to a straight four, open at both ends, by a stone
+/// that Black may play. Lines are k long, the size of Black's shape.
+pub fn forbidden(board: &Board, cell: (i32, i32)) -> bool {
+    forbidden_with(board, &[cell], cell)
+}
+
+/// Whether the stone on `cell` is forbidden to Black, with black stones on
+/// the `extra` cells as well, `cell` among them.
+fn forbidden_with(board: &Board, extra: &[(i32, i32)], cell: (i32, i32)) -> bool {
+    let k = line(board);
+    let runs = DIRECTIONS.map(|step| run(board, extra, cell, step));
+    if runs.contains(&k) {
+        return false;
+    }
+    if runs.iter().any(|run| *run > k) {
+        return true;
+    }
+    let fours: usize = DIRECTIONS
+        .iter()
+        .map(|step| fours(board, extra, cell, *step))
+        .sum();
+    if fours > 1 {
+        return true;
+    }
+    let threes = DIRECTIONS
+        .iter()
+        .filter(|step| three(board, extra, cell, **step))
+        .count();
+    threes > 1
+}
+
+/// The length of the lines Black has to form.
+fn line(board: &Board) -> usize {
+    board.shapes1.orientations()[0].len()
+}
+
+fn point(board: &Board, extra: &[(i32, i32)], cell: (i32, i32)) -> Point {
+    match board.grid.locate(cell) {
+        Some(cell) if extra.contains(&cell) => Point::Black,
+        Some(cell) => match board.field.get(&cell) {
+            Some(Tile::One) => Point::Black,
+            Some(Tile::Empty) => Point::Empty,
+            _ => Point::Blocked,
+        },
+        None => Point::Blocked,
+    }
+}
+
+fn shift((x, y): (i32, i32), (dx, dy): (i32, i32), times: i32) -> (i32, i32) {
+    (x + dx * times, y + dy * times)
+}
+
+/// The number of black stones in a row through the black `cell` along `step`.
+fn run(board: &Board, extra: &[(i32, i32)], cell: (i32, i32), step: (i32, i32)) -> usize {
+    let k = line(board) as i32;
+    let length = |sense: i32| {
+        (1..=k + 1)
+            .take_while(|i| point(board, extra, shift(cell, step, sense * i)) == Point::Black)
+            .count()
+    };
+    1 + length(1) + length(-1)
+}
+
+/// The empty cells along `step` that complete a five through `cell`.
+fn completions(
+    board: &Board,
+    extra: &[(i32, i32)],
+    cell: (i32, i32),
+    step: (i32, i32),
+) -> Vec<(i32, i32)> {
+    let k = line(board);
+    (-(k as i32)..=k as i32)
+        .map(|i| shift(cell, step, i))
+        .filter(|next| point(board, extra, *next) == Point::Empty)
+        .filter_map(|next| board.grid.locate(next))
+        .filter(|next| run(board, &[extra, &[*next]].concat(), cell, step) == k)
+        .collect()
+}
+
+/// The fours through `cell` along `step`. A straight four is a single four
+/// with two ways to complete it, other lines like X.XXX.X hold two.
+fn fours(board: &Board, extra: &[(i32, i32)], cell: (i32, i32), step: (i32, i32)) -> usize {
+    let completions = completions(board, extra, cell, step).len();
+    match straight(board, extra, cell, step) {
+        true => 1,
+        false => completions,
+    }
+}
+
+/// Whether the stones through `cell` along `step` are a straight four, k - 1
+/// in a row that either end completes.
+fn straight(board: &Board, extra: &[(i32, i32)], cell: (i32, i32), step: (i32, i32)) -> bool {
+    run(board, extra, cell, step) + 1 == line(board)
+        && completions(board, extra, cell, step).len() == 2
+}
+
+/// Whether a stone Black may play joins the row through `cell` along `step`
+/// and turns it into a straight four.
+fn three(board: &Board, extra: &[(i32, i32)], cell: (i32, i32), step: (i32, i32)) -> bool {
+    let k = line(board) as i32;
+    let before = run(board, extra, cell, step);
+    (1 - k..k)
+        .map(|i| shift(cell, step, i))
+        .filter(|next| point(board, extra, *next) == Point::Empty)
+        .filter_map(|next| board.grid.locate(next))
+        .any(|next| {
+            let extra = [extra, &[next]].concat();
+            run(board, &extra, cell, step) > before
+                && straight(board, &extra, cell, step)
+                && !forbidden_with(board, &extra, next)
+        })
+}
@@ -0,0 +1,519 @@
+use super::game::Tile;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// How a game on a board is played and won, on top of the shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rules {
+    pub goal: Goal,
+    pub mode: Mode,
+    pub schedule: Schedule,
+    pub turns: Turns,
+    pub placement: Placement,
+    pub opening: Opening,
+    /// How many disjoint copies of their shapes a player has to form.
+    pub copies: usize,
+    pub exact: Exact,
+    /// Whether player one, Black, may not play overlines, double-fours and
+    /// double-threes.
+    pub renju: bool,
+    /// How many captured pairs win, as in Pente, where a stone flanking
+    /// exactly two enemy stones in a line takes them off the board. No
+    /// captures when zero.
+    pub captures: usize,
+    /// Whether the players build straight lines as long as their shapes,
+    /// along the axes and diagonals of the lattice as in m,n,k-games, rather
+    /// than the orientations of their shapes.
+    pub lines: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            goal: Goal::default(),
+            mode: Mode::default(),
+            schedule: Schedule::default(),
+            turns: Turns::default(),
+            placement: Placement::default(),
+            opening: Opening::default(),
+            copies: 1,
+            exact: Exact::default(),
+            renju: false,
+            captures: 0,
+            lines: false,
+        }
+    }
+}
+
+/// Which players have to form their shapes exactly: no stone of their own
+/// may continue one of the straight lines of the shape, so in gomoku an
+/// overline of six does not count as five.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Exact {
+    /// Any superset of the shape counts.
+    #[default]
+    Neither,
+    One,
+    Two,
+    Both,
+}
+
+/// Who places the next stone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Turns {
+    /// The players take turns by the schedule.
+    #[default]
+    Alternating,
+    /// A fair coin decides before every stone.
+    Random,
+}
+
+/// Where a stone may go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Placement {
+    /// On any empty cell.
+    #[default]
+    Free,
+    /// On the lowest empty cell of a column, as if dropped into it like in
+    /// Connect Four. The bottom row, stones and blocked cells hold it up.
+    Gravity,
+}
+
+/// Rules for the first stones of the game, which the players only agree on
+/// with each other when both may still take the first move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Opening {
+    /// Any stone goes anywhere.
+    #[default]
+    Free,
+    /// The first stone goes on the centre cell.
+    Centre,
+    /// The first stone goes on the centre cell and player one's second
+    /// stone at least `distance` rows or columns away from it, 3 in the pro
+    /// rule of gomoku and 4 in long pro.
+    Pro { distance: i32 },
+    /// The pie rule: player two may take over the first stone as their own
+    /// instead of answering it, and player one moves next.
+    Swap,
+    /// Player one places two stones of their own and one of player two's.
+    /// Player two may then take over player one's stones, answer them with
+    /// a stone of their own, or propose one more stone of each and leave it
+    /// to player one to take over either side.
+    Swap2,
+}
+
+impl Opening {
+    /// The number of stones after which the opening no longer has a say.
+    pub fn length(&self) -> usize {
+        match self {
+            Opening::Free => 0,
+            Opening::Centre => 1,
+            Opening::Pro { .. } => 3,
+            Opening::Swap => 2,
+            Opening::Swap2 => 6,
+        }
+    }
+
+    /// Whether a player may take over the other player's stones.
+    pub fn swaps(&self) -> bool {
+        matches!(self, Opening::Swap | Opening::Swap2)
+    }
+}
+
+/// What forming one's shape does to the player who forms it first, or
+/// whether the shapes only count once the board is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Goal {
+    /// Forming the shape wins.
+    #[default]
+    Achievement,
+    /// Forming the shape loses, the misère version of the game.
+    Avoidance,
+    /// Play goes on until the board is full, and the player with more
+    /// copies of their shapes wins. With `disjoint` only the most copies
+    /// that share no cell count.
+    Score { disjoint: bool },
+}
+
+/// Which players build a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Mode {
+    /// Both players build their shape.
+    #[default]
+    Strong,
+    /// Only Maker builds a shape, Breaker wins when the board fills up
+    /// without it. Either of them may move first. With the avoidance goal
+    /// this is Avoider–Enforcer, Avoider loses by forming the shape and wins
+    /// a full board.
+    MakerBreaker { maker_first: bool },
+    /// Player one, Picker, offers two empty cells each turn. Player two,
+    /// Chooser, keeps one of them and Picker gets the other. A lone last
+    /// cell goes to Chooser. Only one of them builds a shape: Picker in
+    /// Picker–Chooser, Chooser in Chooser–Picker. The other player wins a
+    /// full board. Waiter–Client is Chooser–Picker with the avoidance goal.
+    PickerChooser { picker_builds: bool },
+}
+
+/// How many stones the players place per turn. Player one opens with
+/// `first` stones, after which player two places `two` and player one `one`
+/// stones a turn. Connect6 opens with one stone and continues with two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Schedule {
+    pub first: usize,
+    pub one: usize,
+    pub two: usize,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            first: 1,
+            one: 1,
+            two: 1,
+        }
+    }
+}
+
+impl Schedule {
+    /// The player placing the stone after `stones` stones have been placed.
+    pub fn mover(&self, stones: usize) -> Tile {
+        if stones < self.first {
+            return Tile::One;
+        }
+        match (stones - self.first) % (self.one + self.two) < self.two {
+            true => Tile::Two,
+            false => Tile::One,
+        }
+    }
+
+    /// Whether the stone after `stones` stones is the last of its turn.
+    pub fn ends_turn(&self, stones: usize) -> bool {
+        self.mover(stones) != self.mover(stones + 1)
+    }
+
+    /// How many of the first `stones` stones each player placed.
+    pub fn counts(&self, stones: usize) -> (usize, usize) {
+        let twos = (0..stones)
+            .filter(|stone| self.mover(*stone) == Tile::Two)
+            .count();
+        (stones - twos, twos)
+    }
+
+    /// The stones a player places in each turn, starting from the first.
+    pub fn turns(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.first).chain([self.two, self.one].into_iter().cycle())
+    }
+}
+
+impl Mode {
+    /// The player without a shape, if there is one.
+    pub fn breaker(&self) -> Option<Tile> {
+        match self {
+            Mode::Strong => None,
+            Mode::MakerBreaker { maker_first: true } => Some(Tile::Two),
+            Mode::MakerBreaker { maker_first: false } => Some(Tile::One),
+            Mode::PickerChooser {
+                picker_builds: true,
+            } => Some(Tile::Two),
+            Mode::PickerChooser {
+                picker_builds: false,
+            } => Some(Tile::One),
+        }
+    }
+
+    /// Whether turns are offers of two cells rather than stones.
+    pub fn offers(&self) -> bool {
+        matches!(self, Mode::PickerChooser { .. })
+    }
+
+    /// Whether `tile` plays for a shape of their own.
+    pub fn builds(&self, tile: Tile) -> bool {
+        tile != Tile::Empty && self.breaker() != Some(tile)
+    }
+}
+
+impl Exact {
+    /// Whether `tile` has to form their shapes exactly.
+    pub fn applies(&self, tile: Tile) -> bool {
+        match self {
+            Exact::Neither => false,
+            Exact::One => tile == Tile::One,
+            Exact::Two => tile == Tile::Two,
+            Exact::Both => tile != Tile::Empty,
+        }
+    }
+}
+
+impl Goal {
+    /// The winner of a game in which `tile` formed their shape first.
+    pub fn winner(&self, tile: Tile) -> Tile {
+        match self {
+            Goal::Achievement | Goal::Score { .. } => tile,
+            Goal::Avoidance => tile.opponent(),
+        }
+    }
+
+    /// Whether the game is decided by the copies on the full board.
+    pub fn scores(&self) -> bool {
+        matches!(self, Goal::Score { .. })
+    }
+}
+
+impl FromStr for Goal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "achievement" => Ok(Goal::Achievement),
+            "avoidance" => Ok(Goal::Avoidance),
+            "score" => Ok(Goal::Score { disjoint: false }),
+            "disjoint-score" => Ok(Goal::Score { disjoint: true }),
+            _ => Err("expected one of achievement, avoidance, score, disjoint-score".to_string()),
+        }
+    }
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Goal::Achievement => "achievement",
+            Goal::Avoidance => "avoidance",
+            Goal::Score { disjoint: false } => "score",
+            Goal::Score { disjoint: true } => "disjoint-score",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// Reads `Q` for Q stones every turn, `P,Q` for P stones on the first
+    /// turn and Q on every later one, and `A:B` for A stones of player one
+    /// and B of player two, optionally after a first turn as in `P,A:B`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a schedule like 2, 1,2, 1:2 or 1,1:2, found '{s}'");
+        let number = |value: &str| match value.trim().parse::<usize>() {
+            Ok(stones) if stones > 0 => Ok(stones),
+            _ => Err(invalid()),
+        };
+        let (first, later) = match s.split_once(',') {
+            Some((first, later)) => (Some(number(first)?), later),
+            None => (None, s),
+        };
+        let (one, two) = match later.split_once(':') {
+            Some((one, two)) => (number(one)?, number(two)?),
+            None => (number(later)?, number(later)?),
+        };
+        Ok(Schedule {
+            first: first.unwrap_or(one),
+            one,
+            two,
+        })
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first != self.one {
+            write!(f, "{},", self.first)?;
+        }
+        match self.one == self.two {
+            true => write!(f, "{}", self.one),
+            false => write!(f, "{}:{}", self.one, self.two),
+        }
+    }
+}
+
+impl FromStr for Turns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alternating" => Ok(Turns::Alternating),
+            "random" => Ok(Turns::Random),
+            _ => Err("expected one of alternating, random".to_string()),
+        }
+    }
+}
+
+impl Display for Turns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Turns::Alternating => "alternating",
+            Turns::Random => "random",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(Placement::Free),
+            "gravity" => Ok(Placement::Gravity),
+            _ => Err("expected one of free, gravity".to_string()),
+        }
+    }
+}
+
+impl Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Placement::Free => "free",
+            Placement::Gravity => "gravity",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl FromStr for Opening {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(Opening::Free),
+            "centre" | "center" => Ok(Opening::Centre),
+            "pro" => Ok(Opening::Pro { distance: 3 }),
+            "long-pro" => Ok(Opening::Pro { distance: 4 }),
+            "swap" => Ok(Opening::Swap),
+            "swap2" => Ok(Opening::Swap2),
+            _ => Err("expected one of free, centre, pro, long-pro, swap, swap2".to_string()),
+        }
+    }
+}
+
+impl Display for Opening {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Opening::Free => write!(f, "free"),
+            Opening::Centre => write!(f, "centre"),
+            Opening::Pro { distance: 3 } => write!(f, "pro"),
+            Opening::Pro { distance: 4 } => write!(f, "long-pro"),
+            Opening::Pro { distance } => write!(f, "pro {distance}"),
+            Opening::Swap => write!(f, "swap"),
+            Opening::Swap2 => write!(f, "swap2"),
+        }
+    }
+}
+
+impl FromStr for Exact {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Exact::Neither),
+            "one" => Ok(Exact::One),
+            "two" => Ok(Exact::Two),
+            "both" => Ok(Exact::Both),
+            _ => Err("expected one of none, one, two, both".to_string()),
+        }
+    }
+}
+
+impl Display for Exact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Exact::Neither => "none",
+            Exact::One => "one",
+            Exact::Two => "two",
+            Exact::Both => "both",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strong" => Ok(Mode::Strong),
+            "maker-breaker" => Ok(Mode::MakerBreaker { maker_first: true }),
+            "breaker-maker" => Ok(Mode::MakerBreaker { maker_first: false }),
+            "picker-chooser" => Ok(Mode::PickerChooser {
+                picker_builds: true,
+            }),
+            "chooser-picker" => Ok(Mode::PickerChooser {
+                picker_builds: false,
+            }),
+            _ => Err(
+                "expected one of strong, maker-breaker, breaker-maker, picker-chooser, chooser-picker"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Mode::Strong => "strong",
+            Mode::MakerBreaker { maker_first: true } => "maker-breaker",
+            Mode::MakerBreaker { maker_first: false } => "breaker-maker",
+            Mode::PickerChooser {
+                picker_builds: true,
+            } => "picker-chooser",
+            Mode::PickerChooser {
+                picker_builds: false,
+            } => "chooser-picker",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Names the game and its variations, like "avoider-enforcer gravity exact".
+impl Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.mode, self.goal) {
+            (Mode::Strong, Goal::Achievement) => write!(f, "strong"),
+            (Mode::Strong, Goal::Avoidance) => write!(f, "avoidance"),
+            (mode, Goal::Achievement) => write!(f, "{mode}"),
+            (Mode::MakerBreaker { maker_first: true }, Goal::Avoidance) => {
+                write!(f, "avoider-enforcer")
+            }
+            (Mode::MakerBreaker { maker_first: false }, Goal::Avoidance) => {
+                write!(f, "enforcer-avoider")
+            }
+            (
+                Mode::PickerChooser {
+                    picker_builds: false,
+                },
+                Goal::Avoidance,
+            ) => write!(f, "waiter-client"),
+            (mode, Goal::Avoidance) => write!(f, "{mode} avoidance"),
+            (Mode::Strong, goal) => write!(f, "{goal}"),
+            (mode, goal) => write!(f, "{mode} {goal}"),
+        }?;
+        if self.schedule != Schedule::default() {
+            write!(f, " {}", self.schedule)?;
+        }
+        if self.turns == Turns::Random {
+            write!(f, " random-turn")?;
+        }
+        if self.placement == Placement::Gravity {
+            write!(f, " gravity")?;
+        }
+        if self.opening != Opening::Free {
+            write!(f, " {} opening", self.opening)?;
+        }
+        if self.copies != 1 {
+            write!(f, " {} copies", self.copies)?;
+        }
+        match self.exact {
+            Exact::Neither => {}
+            Exact::Both => write!(f, " exact")?,
+            exact => write!(f, " exact {exact}")?,
+        }
+        if self.renju {
+            write!(f, " renju")?;
+        }
+        match self.captures {
+            0 => Ok(()),
+            1 => write!(f, " 1 capture"),
+            captures => write!(f, " {captures} captures"),
+        }
+    }
+}
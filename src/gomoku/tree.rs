@@ -1,24 +1,30 @@
-#![allow(unused)]
+use super::Limits;
 use crate::gomoku::game::*;
+use crate::gomoku::grid::Grid;
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::time::Instant;
 
 pub struct Tree {
-    pub legal: HashMap<(i32, i32), bool>,
-    pub moves: Vec<(i32, i32)>,
+    pub legal: BTreeMap<(i32, i32), bool>,
+    pub limits: Limits,
+    pub nodes: usize,
+    pub aborted: bool,
+    start: Instant,
 }
 
 impl Tree {
-    pub fn new(size: i32) -> Self {
-        let mut legal = HashMap::new();
-        for i in 0..size {
-            for j in 0..size {
-                legal.insert((i, j), true);
-            }
+    pub fn new(grid: &Grid) -> Self {
+        let mut legal = BTreeMap::new();
+        for cell in grid.cells() {
+            legal.insert(cell, true);
         }
         Tree {
             legal,
-            moves: vec![],
+            limits: Limits::default(),
+            nodes: 0,
+            aborted: false,
+            start: Instant::now(),
         }
     }
 
@@ -30,8 +36,8 @@ impl Tree {
         }
     }
 
-    pub fn expand(&self) -> HashMap<(i32, i32), bool> {
-        let mut hm = HashMap::new();
+    pub fn expand(&self) -> BTreeMap<(i32, i32), bool> {
+        let mut hm = BTreeMap::new();
         for ((l1, l2), answer) in &self.legal {
             if *answer {
                 hm.insert((*l1, *l2), true);
@@ -40,24 +46,72 @@ impl Tree {
         hm
     }
 
-    pub fn _minimax(&mut self, board: &mut Board, maximizer: bool, depth: usize) -> i32 {
+    /// The moves to search, the empty cells unless turns are offers or the
+    /// opening has a decision to take, less
+    /// those forbidden to Black in Renju and those above empty cells under
+    /// gravity.
+    fn moves(&self, board: &Board) -> Vec<Move> {
+        match board.rules.mode.offers() || !board.choices().is_empty() {
+            true => board.moves(),
+            false => self
+                .expand()
+                .into_keys()
+                .filter(|cell| board.legal(*cell))
+                .map(Move::Stone)
+                .collect(),
+        }
+    }
+
+    fn play(&mut self, board: &mut Board, play: Move) {
+        board.play(play);
+        if let Move::Stone(cell) = play {
+            self.legal.insert(cell, false);
+            for taken in board.last_captured() {
+                self.legal.insert(*taken, true);
+            }
+        }
+    }
+
+    fn unplay(&mut self, board: &mut Board, play: Move) {
+        if let Move::Stone(cell) = play {
+            for taken in board.last_captured() {
+                self.legal.insert(*taken, false);
+            }
+            self.legal.insert(cell, true);
+        }
+        board.unplay(play);
+    }
+
+    /// Counts a visited node and reports whether the search has to stop.
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if self.limits.exceeded(self.nodes, self.start) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    pub fn minimax(&mut self, board: &mut Board, maximizer: bool, depth: usize) -> i32 {
         if board.is_over() {
-            return self.evaluate(&board);
+            return self.evaluate(board);
+        }
+        if self.visit() {
+            return 0;
+        }
+        // Black in Renju may be left without a move, which ends in a draw
+        let moves = self.moves(board);
+        if moves.is_empty() {
+            return 0;
         }
         let mut best_val;
         let d = depth + 1;
         if maximizer {
             best_val = -2;
             self.legal = self.expand();
-            for ((x_cord, y_cord), _) in self.expand() {
-                if depth == 1 {
-                    println!("Searched a complete subtree!");
-                }
-                board.place_proof(x_cord, y_cord);
-                self.legal.insert((x_cord, y_cord), false);
-
-                let current_val = self._minimax(board, false, d);
-                self.legal.insert((x_cord, y_cord), true);
+            for play in moves {
+                self.play(board, play);
+                let current_val = self.minimax(board, board.player_one, d);
+                self.unplay(board, play);
                 if current_val == 1 {
                     return 1;
                 }
@@ -66,14 +120,10 @@ impl Tree {
         } else {
             best_val = 2;
             self.legal = self.expand();
-            for ((x_cord, y_cord), _) in self.expand() {
-                if depth == 1 {
-                    println!("Searched a complete subtree!");
-                }
-                board.place_proof(x_cord, y_cord);
-                self.legal.insert((x_cord, y_cord), false);
-                let current_val = self._minimax(board, true, d);
-                self.legal.insert((x_cord, y_cord), true);
+            for play in moves {
+                self.play(board, play);
+                let current_val = self.minimax(board, board.player_one, d);
+                self.unplay(board, play);
                 if current_val == -1 {
                     return -1;
                 }
@@ -84,39 +134,51 @@ impl Tree {
         best_val
     }
 
-    pub fn _alphabeta(&mut self, board: &mut Board, maximizer: bool, alpha: i32, beta: i32) -> i32 {
+    pub fn alphabeta(&mut self, board: &mut Board, maximizer: bool, alpha: i32, beta: i32) -> i32 {
         if board.is_over() {
-            return self.evaluate(&board);
+            return self.evaluate(board);
+        }
+        if self.visit() {
+            return 0;
+        }
+        // Black in Renju may be left without a move, which ends in a draw
+        let moves = self.moves(board);
+        if moves.is_empty() {
+            return 0;
         }
         let mut val;
         if maximizer {
             val = -2;
             self.legal = self.expand();
             let mut new_alpha = alpha;
-            for ((x_cord, y_cord), _) in self.expand() {
-                board.place_proof(x_cord, y_cord);
-                self.legal.insert((x_cord, y_cord), false);
-                val = self._alphabeta(board, false, new_alpha, beta);
-                self.legal.insert((x_cord, y_cord), true);
-                if val > beta {
+            for play in moves {
+                self.play(board, play);
+                val = cmp::max(
+                    val,
+                    self.alphabeta(board, board.player_one, new_alpha, beta),
+                );
+                self.unplay(board, play);
+                if val >= beta {
                     break;
                 }
-                new_alpha = cmp::max(alpha, val);
+                new_alpha = cmp::max(new_alpha, val);
             }
         } else {
             val = 2;
             self.legal = self.expand();
             let mut new_beta = beta;
-            for ((x_cord, y_cord), _) in self.expand() {
-                board.place_proof(x_cord, y_cord);
-                self.legal.insert((x_cord, y_cord), false);
-                val = self._alphabeta(board, true, alpha, new_beta);
-                self.legal.insert((x_cord, y_cord), true);
-                if val < alpha {
+            for play in moves {
+                self.play(board, play);
+                val = cmp::min(
+                    val,
+                    self.alphabeta(board, board.player_one, alpha, new_beta),
+                );
+                self.unplay(board, play);
+                if val <= alpha {
                     break;
                 }
 
-                new_beta = cmp::min(beta, val);
+                new_beta = cmp::min(new_beta, val);
             }
         }
         val
@@ -0,0 +1,658 @@
+//! Known values of small games, solved through the command line.
+
+use std::process::Command;
+
+/// The outcome column of `gomoku solve` in CSV format.
+fn solve(args: &[&str]) -> String {
+    solve_column(args, "outcome")
+}
+
+/// A column of `gomoku solve` in CSV format.
+fn solve_column(args: &[&str], name: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_gomoku"))
+        .arg("solve")
+        .args(args)
+        .args(["--format", "csv"])
+        .output()
+        .expect("the binary runs");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).expect("the output is UTF-8");
+    let header = fields(stdout.lines().next().unwrap());
+    let row = fields(stdout.lines().nth(1).unwrap());
+    let column = header.iter().position(|column| column == name).unwrap();
+    row[column].clone()
+}
+
+/// Whether `gomoku solve` accepts the arguments.
+fn accepts(args: &[&str]) -> bool {
+    Command::new(env!("CARGO_BIN_EXE_gomoku"))
+        .arg("solve")
+        .args(args)
+        .args(["--max-nodes", "1"])
+        .output()
+        .expect("the binary runs")
+        .status
+        .success()
+}
+
+/// The fields of a CSV line, which may be quoted to hold commas.
+fn fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+#[test]
+fn tic_tac_toe_is_a_draw() {
+    assert_eq!(solve(&["-n", "3", "--shape", "tic-tac-toe"]), "draw");
+}
+
+#[test]
+fn small_achievement_games() {
+    assert_eq!(solve(&["-n", "1", "--shape", "tic"]), "one");
+    assert_eq!(solve(&["-n", "2", "--shape", "domino"]), "one");
+    assert_eq!(solve(&["-n", "2", "--shape", "el"]), "draw");
+    assert_eq!(solve(&["-n", "3", "--shape", "el"]), "one");
+    assert_eq!(solve(&["-n", "3", "--shape", "tippy"]), "one");
+}
+
+#[test]
+fn board_sizes_are_bounded() {
+    assert!(accepts(&["-n", "32"]));
+    assert!(!accepts(&["-n", "33"]));
+    assert!(!accepts(&["-n", "100000"]));
+    assert!(!accepts(&["-n", "11", "--grid", "cube"]));
+    assert!(!accepts(&["--sizes", "3..40"]));
+    let board = |n: usize| vec![".".repeat(n); n].join("/");
+    assert!(accepts(&["--board", &board(32)]));
+    assert!(!accepts(&["--board", &board(40)]));
+}
+
+#[test]
+fn transpositions_keep_their_moves() {
+    // PNS once dropped every move that reached a position already in its
+    // tree by another order, and lost these wins with them
+    for (args, outcome) in [
+        (&["-n", "3", "--shape", "tippy"][..], "one"),
+        (&["-n", "4x3", "--shape", "tippy"], "one"),
+        (&["-n", "3", "--shape", "el", "--goal", "avoidance"], "two"),
+    ] {
+        assert_eq!(solve(args), outcome, "{args:?}");
+    }
+}
+
+#[test]
+fn minimax_agrees_with_alphabeta() {
+    // the tree searches once kept the stones of earlier siblings on the
+    // board, and alphabeta the value of the last child it searched
+    for args in [
+        &["-n", "3x2", "--shape", "el"][..],
+        &["-n", "3", "--shape", "el"],
+        &["-n", "2", "--shape", "el"],
+        &["-n", "4x2", "--shape", "tippy"],
+    ] {
+        let minimax = [args, &["--engine", "minimax"]].concat();
+        let alphabeta = [args, &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&minimax), solve(&alphabeta), "{args:?}");
+        assert_eq!(solve(&minimax), solve(args), "{args:?}");
+    }
+}
+
+#[test]
+fn rectangles_agree_with_alphabeta() {
+    for args in [
+        &["-n", "4x3", "--shape", "el"][..],
+        &["-n", "5x2", "--shape", "el"],
+        &["-n", "2x5", "--shape", "el"],
+    ] {
+        let alphabeta = [args, &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn masks_agree_with_alphabeta() {
+    for (board, shape) in [
+        ("..#./..../#...", "el"),
+        ("...#/..../....", "el"),
+        ("#../.../..#", "el"),
+        ("##../..../....", "tippy"),
+    ] {
+        let args = ["--board", board, "--shape", shape];
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{board}");
+    }
+}
+
+#[test]
+fn wrapping_boards_agree_with_alphabeta() {
+    for args in [
+        &["-n", "3", "--topology", "torus", "--shape", "el"][..],
+        &["-n", "4x2", "--topology", "torus", "--shape", "el"],
+        &["-n", "4x3", "--topology", "cylinder", "--shape", "el"],
+        &["-n", "4x2", "--topology", "cylinder", "--shape", "el"],
+    ] {
+        let alphabeta = [args, &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn hex_boards_agree_with_alphabeta() {
+    for args in [
+        &["-n", "3", "--shape", "trihex-bar"][..],
+        &["-n", "3", "--shape", "trihex-chevron"],
+        &["-n", "4x3", "--shape", "trihex-chevron"],
+    ] {
+        let args = [args, &["--grid", "hex"]].concat();
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn triangle_boards_agree_with_alphabeta() {
+    for args in [
+        &["-n", "4x3"][..],
+        &["-n", "5x2"],
+        &["-n", "4x2", "--topology", "cylinder"],
+        &["-n", "5x2", "--shape", "tetriamond-c"],
+    ] {
+        let args = [args, &["--grid", "triangle"]].concat();
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn cube_boards_agree_with_alphabeta() {
+    for args in [
+        &["-n", "2x2x2", "--shape", "tricube-l"][..],
+        &["-n", "3x2x2", "--shape", "tricube-l"],
+        &["-n", "2x2x2", "--shape", "tetracube-l"],
+        &["-n", "3x3x2", "--game", "2,2,2"],
+        &["-n", "3x2x2", "--game", "2,2,2"],
+    ] {
+        let args = [args, &["--grid", "cube"]].concat();
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn cubes_of_lines() {
+    let cube = |game: &str, size: &str| solve(&["--game", game, "--grid", "cube", "-n", size]);
+    // a single layer plays the square game
+    assert_eq!(cube("4,4,3", "4x4x1"), solve(&["--game", "4,4,3"]));
+    assert_eq!(cube("3,3,3", "3x3x2"), "draw");
+    // the preset sets up the cube, see qubic_is_a_first_player_win
+    let qubic = |column: &str| solve_column(&["--game", "qubic", "--max-nodes", "1"], column);
+    assert_eq!(qubic("grid"), "cube");
+    assert_eq!(qubic("depth"), "4");
+    assert!(accepts(&["--game", "4,4,4", "--grid", "cube"]));
+    assert!(!accepts(&["--game", "qubic", "--grid", "hex"]));
+}
+
+/// Qubic is a first-player win (Patashnik 1980, Allis 1994). Proof-number
+/// search without threat-space search leaves it unknown after millions of
+/// nodes, so the budget the requester asked for is not met yet.
+#[test]
+#[ignore = "PNS does not solve Qubic within the node budget yet"]
+fn qubic_is_a_first_player_win() {
+    let budget = ["--game", "qubic", "--max-nodes", "10000000"];
+    assert_eq!(solve(&budget), "one");
+}
+
+#[test]
+fn avoidance_games() {
+    let avoid =
+        |size: &str, shape: &str| solve(&["-n", size, "--shape", shape, "--goal", "avoidance"]);
+    // the first stone forms the monomino
+    assert_eq!(avoid("1", "tic"), "two");
+    assert_eq!(avoid("1", "domino"), "draw");
+    assert_eq!(avoid("2", "domino"), "two");
+    assert_eq!(avoid("3", "domino"), "two");
+    assert_eq!(avoid("2", "el"), "draw");
+    assert_eq!(avoid("3", "el"), "two");
+    assert_eq!(avoid("3", "tippy"), "two");
+    assert_eq!(avoid("3", "tic-tac-toe"), "draw");
+}
+
+#[test]
+fn avoidance_agrees_with_alphabeta() {
+    for shape in ["domino", "el", "tic-tac-toe"] {
+        let args = ["-n", "2x3", "--shape", shape, "--goal", "avoidance"];
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{shape}");
+    }
+}
+
+#[test]
+fn maker_breaker_games() {
+    let game = |mode: &str, size: &str, shape: &str| {
+        solve(&["-n", size, "--shape", shape, "--mode", mode])
+    };
+    assert_eq!(game("maker-breaker", "3", "domino"), "one");
+    assert_eq!(game("maker-breaker", "3", "tic-tac-toe"), "two");
+    assert_eq!(game("maker-breaker", "3x4", "tic-tac-toe"), "one");
+    assert_eq!(game("breaker-maker", "3", "el"), "one");
+    assert_eq!(game("breaker-maker", "3x4", "el"), "two");
+}
+
+#[test]
+fn maker_breaker_agrees_with_alphabeta() {
+    for mode in ["maker-breaker", "breaker-maker"] {
+        for shape in ["el", "tippy"] {
+            let args = ["-n", "3", "--shape", shape, "--mode", mode];
+            let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+            assert_eq!(solve(&args), solve(&alphabeta), "{mode} {shape}");
+        }
+    }
+}
+
+#[test]
+fn avoider_enforcer_games() {
+    let game = |mode: &str, size: &str, shape: &str| {
+        solve(&["-n", size, "--shape", shape, "--mode", mode])
+    };
+    assert_eq!(game("avoider-enforcer", "3", "domino"), "two");
+    assert_eq!(game("avoider-enforcer", "2x3", "el"), "one");
+    assert_eq!(game("avoider-enforcer", "3", "knobby"), "one");
+    assert_eq!(game("enforcer-avoider", "3", "domino"), "one");
+    assert_eq!(game("enforcer-avoider", "3", "el"), "two");
+}
+
+#[test]
+fn avoider_enforcer_agrees_with_alphabeta() {
+    for mode in ["avoider-enforcer", "enforcer-avoider"] {
+        for shape in ["el", "tippy"] {
+            let args = ["-n", "2x3", "--shape", shape, "--mode", mode];
+            let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+            assert_eq!(solve(&args), solve(&alphabeta), "{mode} {shape}");
+        }
+    }
+}
+
+#[test]
+fn move_schedules() {
+    let game = |stones: &str, mode: &str, size: &str, shape: &str| {
+        solve(&[
+            "-n", size, "--shape", shape, "--mode", mode, "--stones", stones,
+        ])
+    };
+    // Connect6 style, one stone and then two a turn
+    assert_eq!(game("1,2", "strong", "3", "tic-tac-toe"), "draw");
+    assert_eq!(game("1,2", "strong", "3", "el"), "one");
+    assert_eq!(game("1,2", "maker-breaker", "3x4", "tic-tac-toe"), "one");
+    assert_eq!(game("2", "strong", "3", "tic-tac-toe"), "one");
+    // biased Maker-Breaker
+    assert_eq!(game("1:2", "maker-breaker", "3x4", "tic-tac-toe"), "two");
+    assert_eq!(game("2:1", "breaker-maker", "3x4", "el"), "one");
+}
+
+#[test]
+fn move_schedules_agree_with_alphabeta() {
+    for stones in ["1,2", "1:2", "2,1"] {
+        for shape in ["el", "tippy"] {
+            let args = ["-n", "3", "--shape", shape, "--stones", stones];
+            let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+            assert_eq!(solve(&args), solve(&alphabeta), "{stones} {shape}");
+        }
+    }
+}
+
+#[test]
+fn game_records_follow_the_schedule() {
+    let args = ["-n", "3", "--shape", "tic-tac-toe", "--stones", "1,2"];
+    assert_eq!(solve(&[&args[..], &["-m", "1,1; 0,0 2,2"]].concat()), "one");
+    let output = Command::new(env!("CARGO_BIN_EXE_gomoku"))
+        .arg("solve")
+        .args(args)
+        .args(["-m", "1,1 0,0; 2,2"])
+        .output()
+        .expect("the binary runs");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn offering_games() {
+    let game = |mode: &str, size: &str, shape: &str| {
+        solve(&["-n", size, "--shape", shape, "--mode", mode])
+    };
+    assert_eq!(game("picker-chooser", "3", "el"), "one");
+    assert_eq!(game("picker-chooser", "3", "tippy"), "two");
+    assert_eq!(game("chooser-picker", "3", "domino"), "two");
+    assert_eq!(game("chooser-picker", "3", "tic-tac-toe"), "one");
+    // Waiter wins once Client is forced to take a whole shape
+    assert_eq!(game("waiter-client", "3", "tic-tac-toe"), "one");
+    assert_eq!(game("waiter-client", "2x3", "el"), "two");
+}
+
+#[test]
+fn offering_games_agree_with_alphabeta() {
+    for mode in ["picker-chooser", "chooser-picker", "waiter-client"] {
+        for shape in ["el", "tippy"] {
+            let args = ["-n", "2x3", "--shape", shape, "--mode", mode];
+            let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+            assert_eq!(solve(&args), solve(&alphabeta), "{mode} {shape}");
+        }
+    }
+}
+
+#[test]
+fn random_turn_games() {
+    let chances = |args: &[&str]| {
+        let args = [args, &["--turns", "random"]].concat();
+        (
+            solve_column(&args, "chance_one"),
+            solve_column(&args, "chance_two"),
+        )
+    };
+    // the first stone forms the monomino
+    assert_eq!(
+        chances(&["-n", "1", "--shape", "tic"]),
+        ("1/2".into(), "1/2".into())
+    );
+    // a domino needs the same player twice
+    assert_eq!(
+        chances(&["-n", "1x2", "--shape", "domino"]),
+        ("1/4".into(), "1/4".into())
+    );
+    assert_eq!(
+        chances(&["-n", "3", "--shape", "tic-tac-toe"]),
+        ("245/512".into(), "245/512".into())
+    );
+    assert_eq!(
+        chances(&[
+            "-n",
+            "3",
+            "--shape",
+            "tic-tac-toe",
+            "--mode",
+            "maker-breaker"
+        ]),
+        ("247/512".into(), "265/512".into())
+    );
+    let args = ["-n", "3", "--shape", "tic-tac-toe", "--turns", "random"];
+    assert_eq!(solve(&args), "chance");
+}
+
+#[test]
+fn alternative_shapes() {
+    // the straight tromino alone is a draw, with the el as well the first player wins
+    assert_eq!(solve(&["-n", "3", "--shape", "tic-tac-toe"]), "draw");
+    assert_eq!(solve(&["-n", "3", "--shape", "tic-tac-toe|el"]), "one");
+    assert_eq!(solve(&["-n", "2x3", "--shape", "tippy|el"]), "draw");
+}
+
+#[test]
+fn disjoint_copies() {
+    let game = |size: &str, shape: &str| solve(&["-n", size, "--shape", shape, "--copies", "2"]);
+    assert_eq!(game("2x2", "tic"), "one");
+    // two dominoes across the middle of 1x4 share a cell
+    assert_eq!(game("1x4", "domino"), "draw");
+    assert_eq!(game("3", "domino"), "one");
+    assert_eq!(game("2x3", "domino"), "draw");
+}
+
+#[test]
+fn shape_sets_agree_with_alphabeta() {
+    for (shape, copies) in [("domino|el", "2"), ("skinny|el", "1"), ("domino", "2")] {
+        for goal in ["achievement", "avoidance"] {
+            let args = [
+                "-n", "3", "--shape", shape, "--copies", copies, "--goal", goal,
+            ];
+            let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+            assert_eq!(solve(&args), solve(&alphabeta), "{shape} {copies} {goal}");
+        }
+    }
+}
+
+#[test]
+fn exact_shapes() {
+    // player one's only four would be part of an overline of five
+    let overline = |exact: &str| {
+        solve(&[
+            "--board",
+            "OO.OO/X.X.X/.X...",
+            "--shape",
+            "skinny",
+            "--exact",
+            exact,
+        ])
+    };
+    assert_eq!(overline("none"), "one");
+    assert_eq!(overline("two"), "one");
+    assert_eq!(overline("one"), "draw");
+    assert_eq!(overline("both"), "draw");
+    let avoidance = |exact: &str| {
+        solve(&[
+            "-n",
+            "3",
+            "--shape",
+            "domino",
+            "--goal",
+            "avoidance",
+            "--exact",
+            exact,
+        ])
+    };
+    assert_eq!(avoidance("both"), "two");
+    assert_eq!(avoidance("one"), "draw");
+}
+
+#[test]
+fn exact_shapes_agree_with_alphabeta() {
+    for shape in ["domino", "el", "tic-tac-toe"] {
+        for goal in ["achievement", "avoidance"] {
+            let args = [
+                "-n", "3", "--shape", shape, "--goal", goal, "--exact", "both",
+            ];
+            let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+            assert_eq!(solve(&args), solve(&alphabeta), "{shape} {goal}");
+        }
+    }
+}
+
+#[test]
+fn m_n_k_games() {
+    assert_eq!(solve(&["--game", "tic-tac-toe"]), "draw");
+    assert_eq!(solve(&["--game", "3,3,3"]), "draw");
+    assert_eq!(solve(&["--game", "4,3,3"]), "one");
+    assert_eq!(solve(&["--game", "4,4,3"]), "one");
+    assert_eq!(solve(&["--game", "4,4,4"]), "draw");
+    // the presets lay their lines along the diagonals, shapes stay connected
+    assert!(!accepts(&["--shape", "0,0 1,1 2,2"]));
+    // gomoku is far out of reach, the presets only set up the board
+    let gomoku =
+        |preset: &str, column: &str| solve_column(&["--game", preset, "--max-nodes", "1"], column);
+    assert_eq!(gomoku("freestyle-gomoku", "width"), "15");
+    assert_eq!(gomoku("freestyle-gomoku", "exact"), "none");
+    assert_eq!(gomoku("standard-gomoku", "exact"), "both");
+}
+
+#[test]
+fn m_n_k_games_agree_with_alphabeta() {
+    for game in ["3,3,3", "4,3,3", "3,2,2"] {
+        let args = ["--game", game];
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{game}");
+    }
+}
+
+#[test]
+fn renju_forbids_black_moves() {
+    let renju = |moves: &str| accepts(&["--game", "renju", "--moves", moves]);
+    // double-three, double-four and overline
+    assert!(!renju("7,7 0,0 8,7 0,2 9,9 0,4 9,8 0,6 9,7"));
+    assert!(!renju(
+        "4,7 0,0 5,7 0,2 6,7 0,4 7,4 0,6 7,5 0,8 7,6 0,10 7,7"
+    ));
+    assert!(!renju("3,7 0,0 4,7 0,2 5,7 0,4 7,7 0,6 8,7 0,8 6,7"));
+    // a four with a three, a three White has closed and a five are allowed
+    assert!(renju("4,7 0,0 5,7 0,2 6,7 0,4 7,5 0,6 7,6 0,8 7,7"));
+    assert!(renju("7,7 6,7 8,7 0,2 9,9 0,4 9,8 0,6 9,7"));
+    assert!(renju("3,7 0,0 4,7 0,2 6,7 0,4 7,7 0,6 3,3 0,8 5,7"));
+    // White may play an overline
+    assert!(renju("0,0 3,7 0,2 4,7 0,4 5,7 0,6 7,7 0,8 8,7 1,14 6,7"));
+}
+
+#[test]
+fn renju_black_cannot_block_with_a_forbidden_move() {
+    // White's four can only be stopped on 5,4, a double-three for Black
+    let board =
+        "........X/........./........./........./OXXXX..../.....OO../.....O.O./........./.........";
+    assert_eq!(solve(&["--game", "renju", "--board", board]), "two");
+    let freestyle = ["--game", "9,9,5", "--board", board, "--max-nodes", "1000"];
+    assert_eq!(solve(&freestyle), "unknown");
+}
+
+#[test]
+fn pente_captures() {
+    // player one takes the pair on 1,0 and 2,0, which wins with one capture
+    let board = "OXX./O.../..../....";
+    assert_eq!(solve(&["--game", "4,4,4", "--board", board]), "draw");
+    let captures = ["--game", "4,4,4", "--board", board, "--captures", "1"];
+    assert_eq!(solve(&captures), "one");
+    // the captured cells are empty again
+    let moves = "9,9 10,9 0,0 11,9 12,9 11,9";
+    assert!(accepts(&["--game", "pente", "--moves", moves]));
+    assert!(!accepts(&["--game", "gomoku", "--moves", moves]));
+    assert!(!accepts(&["--captures", "1", "--mode", "maker-breaker"]));
+}
+
+#[test]
+fn pente_captures_agree_with_alphabeta() {
+    for (game, captures) in [("4,3,3", "1"), ("4,3,3", "2"), ("5,2,4", "1")] {
+        let args = ["--game", game, "--captures", captures];
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{game} {captures}");
+    }
+}
+
+#[test]
+fn gravity() {
+    // player one wins when free to play anywhere, but not when the stones drop
+    let board = "..../..../.X../OOX.";
+    assert_eq!(solve(&["--game", "4,4,3", "--board", board]), "one");
+    let gravity = [
+        "--game",
+        "4,4,3",
+        "--board",
+        board,
+        "--placement",
+        "gravity",
+    ];
+    assert_eq!(solve(&gravity), "two");
+    let floating = ["--game", "4,4,3", "--placement", "gravity"];
+    assert!(!accepts(
+        &[&floating[..], &["--board", "..../.X../..../OO.."]].concat()
+    ));
+    assert!(!accepts(&[&floating[..], &["--moves", "0,0"]].concat()));
+    assert!(accepts(
+        &[&floating[..], &["--moves", "0,3 0,2 1,3"]].concat()
+    ));
+}
+
+#[test]
+fn gravity_agrees_with_alphabeta() {
+    for args in [
+        &["--game", "4,3,3"][..],
+        &["-n", "4", "--shape", "domino"],
+        &["-n", "4x3", "--shape", "el"],
+        &["-n", "3", "--shape", "el"],
+        &["-n", "4", "--shape", "el"],
+    ] {
+        let args = [args, &["--placement", "gravity"]].concat();
+        let alphabeta = [&args[..], &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(&args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn openings() {
+    // player one wins 3 in a row on 4x3, unless player two may take over
+    // the first stone
+    assert_eq!(solve(&["--game", "4,3,3", "--opening", "centre"]), "one");
+    assert_eq!(solve(&["--game", "4,3,3", "--opening", "swap"]), "two");
+    assert_eq!(
+        solve(&["-n", "4", "--shape", "domino", "--opening", "swap"]),
+        "two"
+    );
+    let centre =
+        |moves: &str| accepts(&["--game", "3,3,3", "--opening", "centre", "--moves", moves]);
+    assert!(!centre("0,0"));
+    assert!(centre("1,1 0,0"));
+    let pro = |moves: &str| accepts(&["--game", "gomoku", "--opening", "pro", "--moves", moves]);
+    assert!(!pro("7,7 0,0 8,8"));
+    assert!(pro("7,7 0,0 10,8"));
+    assert!(!accepts(&["--opening", "swap", "--mode", "maker-breaker"]));
+}
+
+#[test]
+fn openings_agree_with_alphabeta() {
+    for args in [
+        &["--game", "3,3,3", "--opening", "swap"][..],
+        &["--game", "3,3,3", "--opening", "swap2"],
+        &["--game", "4,3,3", "--opening", "centre"],
+        &["-n", "3", "--shape", "el", "--opening", "swap2"],
+    ] {
+        let alphabeta = [args, &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(args), solve(&alphabeta), "{args:?}");
+    }
+}
+
+#[test]
+fn scoring() {
+    // player one's extra stone on 3x3 adds overlapping copies, not disjoint ones
+    let score = |shape: &str, goal: &str| {
+        let args = ["-n", "3", "--shape", shape, "--goal", goal];
+        (solve(&args), solve_column(&args, "margin"))
+    };
+    assert_eq!(score("domino", "score"), ("one".into(), "2".into()));
+    assert_eq!(
+        score("domino", "disjoint-score"),
+        ("draw".into(), "0".into())
+    );
+    assert_eq!(
+        solve_column(&["--game", "3,3,2", "--goal", "score"], "margin"),
+        "4"
+    );
+    assert_eq!(solve(&["--game", "3,3,3", "--goal", "score"]), "draw");
+    assert_eq!(solve_column(&["--game", "3,3,3"], "margin"), "");
+    assert!(!accepts(&["--goal", "score", "--mode", "maker-breaker"]));
+    assert!(!accepts(&["--goal", "score", "--copies", "2"]));
+}
+
+#[test]
+fn scoring_agrees_with_alphabeta() {
+    for args in [
+        &["-n", "3", "--shape", "domino", "--goal", "score"][..],
+        &["--game", "3,3,2", "--goal", "disjoint-score"],
+        &["-n", "4x2", "--shape", "domino", "--goal", "score"],
+        &[
+            "-n",
+            "3",
+            "--shape",
+            "domino",
+            "--goal",
+            "score",
+            "--placement",
+            "gravity",
+        ],
+    ] {
+        let alphabeta = [args, &["--engine", "alphabeta"]].concat();
+        assert_eq!(solve(args), solve(&alphabeta), "{args:?}");
+    }
+}